@@ -0,0 +1,245 @@
+//! OpenPGP algorithm identifiers
+//!
+//! [`super::key::PublicKey::parse`] and [`super::signature::Signature::parse`]
+//! decide which public-key algorithms they accept through
+//! [`PublicKeyAlgorithm`], so this is the only place that maps identifiers to
+//! algorithms.
+
+/// A hash algorithm, as identified in signatures and one-pass signatures
+/// (RFC 4880 section 9.4)
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum HashAlgorithm {
+    /// MD5 (1).  This is insecure.
+    Md5,
+    /// SHA-1 (2).  This is insecure.
+    Sha1,
+    /// RIPEMD-160 (3).  This is insecure.
+    Ripemd160,
+    /// SHA-256 (8)
+    Sha256,
+    /// SHA-384 (9)
+    Sha384,
+    /// SHA-512 (10)
+    Sha512,
+    /// SHA-224 (11)
+    Sha224,
+    /// Any other algorithm
+    Unknown(u8),
+}
+
+impl HashAlgorithm {
+    /// Converts a raw algorithm identifier to a [`HashAlgorithm`]
+    ///
+    /// ```rust
+    /// # use openpgp_parser::algorithm::HashAlgorithm;
+    /// assert_eq!(HashAlgorithm::from_u8(8), HashAlgorithm::Sha256);
+    /// assert_eq!(HashAlgorithm::from_u8(4), HashAlgorithm::Unknown(4));
+    /// ```
+    pub fn from_u8(alg: u8) -> Self {
+        match alg {
+            1 => HashAlgorithm::Md5,
+            2 => HashAlgorithm::Sha1,
+            3 => HashAlgorithm::Ripemd160,
+            8 => HashAlgorithm::Sha256,
+            9 => HashAlgorithm::Sha384,
+            10 => HashAlgorithm::Sha512,
+            11 => HashAlgorithm::Sha224,
+            alg => HashAlgorithm::Unknown(alg),
+        }
+    }
+
+    /// Converts a [`HashAlgorithm`] to a raw algorithm identifier
+    ///
+    /// ```rust
+    /// # use openpgp_parser::algorithm::HashAlgorithm;
+    /// assert_eq!(HashAlgorithm::Sha512.to_u8(), 10);
+    /// assert_eq!(HashAlgorithm::Unknown(100).to_u8(), 100);
+    /// ```
+    pub fn to_u8(self) -> u8 {
+        match self {
+            HashAlgorithm::Md5 => 1,
+            HashAlgorithm::Sha1 => 2,
+            HashAlgorithm::Ripemd160 => 3,
+            HashAlgorithm::Sha256 => 8,
+            HashAlgorithm::Sha384 => 9,
+            HashAlgorithm::Sha512 => 10,
+            HashAlgorithm::Sha224 => 11,
+            HashAlgorithm::Unknown(alg) => alg,
+        }
+    }
+
+    /// The length of the digest in bytes, or [`None`] for unknown algorithms
+    ///
+    /// ```rust
+    /// # use openpgp_parser::algorithm::HashAlgorithm;
+    /// assert_eq!(HashAlgorithm::Sha256.digest_len(), Some(32));
+    /// assert_eq!(HashAlgorithm::Unknown(100).digest_len(), None);
+    /// ```
+    pub fn digest_len(&self) -> Option<usize> {
+        match *self {
+            HashAlgorithm::Md5 => Some(16),
+            HashAlgorithm::Sha1 | HashAlgorithm::Ripemd160 => Some(20),
+            HashAlgorithm::Sha256 => Some(32),
+            HashAlgorithm::Sha384 => Some(48),
+            HashAlgorithm::Sha512 => Some(64),
+            HashAlgorithm::Sha224 => Some(28),
+            HashAlgorithm::Unknown(_) => None,
+        }
+    }
+}
+
+/// A public-key algorithm, as identified in signatures and key packets
+/// (RFC 4880 section 9.1 and RFC 6637)
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PublicKeyAlgorithm {
+    /// RSA, for encryption and signing (1)
+    Rsa,
+    /// Legacy encrypt-only RSA (2)
+    RsaEncrypt,
+    /// Legacy sign-only RSA (3)
+    RsaSign,
+    /// Encrypt-only ElGamal (16)
+    ElGamalEncrypt,
+    /// Finite-field Digital Signature Algorithm (17)
+    Dsa,
+    /// Elliptic-curve Diffie-Hellman (18)
+    Ecdh,
+    /// Elliptic-curve Digital Signature Algorithm (19)
+    Ecdsa,
+    /// ElGamal for encryption and signing (20).  ElGamal signatures have been
+    /// broken.
+    ElGamal,
+    /// Finite-field Diffie-Hellman (21)
+    DiffieHellman,
+    /// Edwards-curve Digital Signature Algorithm (22)
+    EdDsa,
+    /// Any other algorithm
+    Unknown(u8),
+}
+
+impl PublicKeyAlgorithm {
+    /// Converts a raw algorithm identifier to a [`PublicKeyAlgorithm`]
+    ///
+    /// ```rust
+    /// # use openpgp_parser::algorithm::PublicKeyAlgorithm;
+    /// assert_eq!(PublicKeyAlgorithm::from_u8(22), PublicKeyAlgorithm::EdDsa);
+    /// assert_eq!(PublicKeyAlgorithm::from_u8(4), PublicKeyAlgorithm::Unknown(4));
+    /// ```
+    pub fn from_u8(alg: u8) -> Self {
+        match alg {
+            1 => PublicKeyAlgorithm::Rsa,
+            2 => PublicKeyAlgorithm::RsaEncrypt,
+            3 => PublicKeyAlgorithm::RsaSign,
+            16 => PublicKeyAlgorithm::ElGamalEncrypt,
+            17 => PublicKeyAlgorithm::Dsa,
+            18 => PublicKeyAlgorithm::Ecdh,
+            19 => PublicKeyAlgorithm::Ecdsa,
+            20 => PublicKeyAlgorithm::ElGamal,
+            21 => PublicKeyAlgorithm::DiffieHellman,
+            22 => PublicKeyAlgorithm::EdDsa,
+            alg => PublicKeyAlgorithm::Unknown(alg),
+        }
+    }
+
+    /// Converts a [`PublicKeyAlgorithm`] to a raw algorithm identifier
+    ///
+    /// ```rust
+    /// # use openpgp_parser::algorithm::PublicKeyAlgorithm;
+    /// assert_eq!(PublicKeyAlgorithm::Rsa.to_u8(), 1);
+    /// assert_eq!(PublicKeyAlgorithm::Unknown(100).to_u8(), 100);
+    /// ```
+    pub fn to_u8(self) -> u8 {
+        match self {
+            PublicKeyAlgorithm::Rsa => 1,
+            PublicKeyAlgorithm::RsaEncrypt => 2,
+            PublicKeyAlgorithm::RsaSign => 3,
+            PublicKeyAlgorithm::ElGamalEncrypt => 16,
+            PublicKeyAlgorithm::Dsa => 17,
+            PublicKeyAlgorithm::Ecdh => 18,
+            PublicKeyAlgorithm::Ecdsa => 19,
+            PublicKeyAlgorithm::ElGamal => 20,
+            PublicKeyAlgorithm::DiffieHellman => 21,
+            PublicKeyAlgorithm::EdDsa => 22,
+            PublicKeyAlgorithm::Unknown(alg) => alg,
+        }
+    }
+
+    /// Returns [`true`] if the algorithm can be used for signatures.  This is
+    /// [`false`] for encryption-only algorithms, for ElGamal (whose signatures
+    /// are broken), and for unknown algorithms.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::algorithm::PublicKeyAlgorithm;
+    /// assert!(PublicKeyAlgorithm::Rsa.can_sign());
+    /// assert!(!PublicKeyAlgorithm::RsaEncrypt.can_sign());
+    /// ```
+    pub fn can_sign(&self) -> bool {
+        match *self {
+            PublicKeyAlgorithm::Rsa
+            | PublicKeyAlgorithm::RsaSign
+            | PublicKeyAlgorithm::Dsa
+            | PublicKeyAlgorithm::Ecdsa
+            | PublicKeyAlgorithm::EdDsa => true,
+            PublicKeyAlgorithm::RsaEncrypt
+            | PublicKeyAlgorithm::ElGamalEncrypt
+            | PublicKeyAlgorithm::Ecdh
+            | PublicKeyAlgorithm::ElGamal
+            | PublicKeyAlgorithm::DiffieHellman
+            | PublicKeyAlgorithm::Unknown(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use signature::pkey_alg_mpis;
+    use Error;
+
+    #[test]
+    fn public_key_algorithm_mapping() {
+        for alg in 0..=255u8 {
+            let ty = PublicKeyAlgorithm::from_u8(alg);
+            assert_eq!(ty.to_u8(), alg);
+            match alg {
+                1...3 | 16...22 => assert_ne!(ty, PublicKeyAlgorithm::Unknown(alg)),
+                _ => assert_eq!(ty, PublicKeyAlgorithm::Unknown(alg)),
+            }
+            // signature parsing agrees on which algorithms can sign
+            match pkey_alg_mpis(alg, 4) {
+                Err(Error::InvalidPkeyAlgorithm(_)) | Err(Error::UnknownPkeyAlgorithm(_)) => {
+                    assert!(!ty.can_sign())
+                }
+                _ => assert!(ty.can_sign()),
+            }
+        }
+    }
+
+    #[test]
+    fn hash_algorithm_mapping() {
+        for &(alg, ty, len) in &[
+            (1, HashAlgorithm::Md5, 16),
+            (2, HashAlgorithm::Sha1, 20),
+            (3, HashAlgorithm::Ripemd160, 20),
+            (8, HashAlgorithm::Sha256, 32),
+            (9, HashAlgorithm::Sha384, 48),
+            (10, HashAlgorithm::Sha512, 64),
+            (11, HashAlgorithm::Sha224, 28),
+        ] {
+            assert_eq!(HashAlgorithm::from_u8(alg), ty);
+            assert_eq!(ty.digest_len(), Some(len));
+        }
+        for alg in 0..=255u8 {
+            let ty = HashAlgorithm::from_u8(alg);
+            assert_eq!(ty.to_u8(), alg);
+            match alg {
+                1...3 | 8...11 => assert_ne!(ty, HashAlgorithm::Unknown(alg)),
+                _ => {
+                    assert_eq!(ty, HashAlgorithm::Unknown(alg));
+                    assert_eq!(ty.digest_len(), None);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Fixed-size reads into arrays.  These need const generics.
+
+use super::{Error, Reader};
+
+impl<'a> Reader<'a> {
+    /// Reads exactly `N` bytes into an array, consuming them.  If fewer bytes
+    /// are left, [`Error::Truncated`] is returned and nothing is consumed.
+    /// This requires Rust 1.51 or later.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, Error};
+    /// let mut reader = Reader::new(&[0x60, 0x0F, 0x77, 0x1A, 5]);
+    /// assert_eq!(reader.read_array::<4>().unwrap(), [0x60, 0x0F, 0x77, 0x1A]);
+    /// assert_eq!(
+    ///     reader.read_array::<2>().unwrap_err(),
+    ///     Error::Truncated { needed: 2, available: 1 },
+    /// );
+    /// assert_eq!(reader.len(), 1);
+    /// ```
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut array = [0u8; N];
+        self.read_exact_into(&mut array)?;
+        Ok(array)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_array() {
+        let data: Vec<u8> = (0..40).collect();
+        let mut reader = Reader::new(&data);
+        assert_eq!(reader.read_array::<4>().unwrap(), [0, 1, 2, 3]);
+        assert_eq!(
+            reader.read_array::<8>().unwrap(),
+            [4, 5, 6, 7, 8, 9, 10, 11]
+        );
+        let fingerprint: [u8; 20] = reader.read_array().unwrap();
+        assert_eq!(fingerprint[..], data[12..32]);
+        assert_eq!(
+            reader.read_array::<20>().unwrap_err(),
+            Error::Truncated {
+                needed: 20,
+                available: 8
+            }
+        );
+        assert_eq!(reader.offset(), 32);
+        assert_eq!(
+            reader.read_array::<8>().unwrap(),
+            [32, 33, 34, 35, 36, 37, 38, 39]
+        );
+        assert_eq!(reader.read_array::<0>().unwrap(), []);
+        assert_eq!(
+            reader.read_array::<4>().unwrap_err(),
+            Error::Truncated {
+                needed: 4,
+                available: 0
+            }
+        );
+    }
+}
@@ -13,29 +13,76 @@ extern crate std;
 
 #[cfg(feature = "std")]
 impl From<EOFError> for std::io::Error {
-    fn from(_: EOFError) -> Self {
-        std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Unexpected EOF")
+    fn from(e: EOFError) -> Self {
+        std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            format!(
+                "Unexpected EOF: needed {} bytes, but only {} available",
+                e.needed, e.available
+            ),
+        )
     }
 }
 
 /// A reader for untrusted data.  No method on this type will ever panic.
 ///
-/// This type is guaranteed to have the same representation as `&'a [u8]`.
-#[derive(Clone, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
+/// Besides the remaining data, a [`Reader`] keeps track of how many bytes it
+/// has consumed; see [`Reader::offset`].
+///
+/// A [`Reader`] is only a slice and an offset, so it is [`Copy`].  Copying it
+/// takes constant time, and the copy is independent of the original, so a copy
+/// can serve as a save point for backtracking.  See
+/// [`Reader::clone_remaining`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub struct Reader<'a> {
     untrusted_buffer: &'a [u8],
+    offset: usize,
 }
 
-/// Error indicating end-of-file
+/// Error indicating end-of-file.  It records how many bytes were needed and
+/// how many were available, which are kept when it is converted to
+/// [`super::Error::Truncated`].
+///
+/// ```rust
+/// # use openpgp_parser::Reader;
+/// let err = Reader::new(&[1, 2, 3]).be_u32().unwrap_err();
+/// assert_eq!((err.needed(), err.available()), (4, 3));
+/// ```
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
-pub struct EOFError;
+pub struct EOFError {
+    needed: usize,
+    available: usize,
+}
+
+impl EOFError {
+    #[inline]
+    pub(crate) fn new(needed: usize, available: usize) -> Self {
+        Self { needed, available }
+    }
+
+    /// The number of bytes that were needed.  For methods that read at an
+    /// offset, this includes the offset.
+    pub fn needed(&self) -> usize {
+        self.needed
+    }
+
+    /// The number of bytes that were available
+    pub fn available(&self) -> usize {
+        self.available
+    }
+}
 
 macro_rules! gen_be_offset {
     ($($(#[$s:meta])* ($i: ident, $t: ty))+) => {$(
         $(#[$s])*
         pub fn $i(&self, offset: usize) -> Result<$t, EOFError> {
             let range = offset..offset.wrapping_add(mem::size_of::<$t>());
-            let data = self.untrusted_buffer.get(range).ok_or(EOFError)?;
+            let data = self.untrusted_buffer.get(range).ok_or_else(|| {
+                EOFError::new(
+                    offset.saturating_add(mem::size_of::<$t>()),
+                    self.untrusted_buffer.len(),
+                )
+            })?;
             let mut res: $t = 0;
             for i in 0..mem::size_of::<$t>() {
                 res = res << 8 | data[i] as $t
@@ -81,7 +128,12 @@ macro_rules! gen_le_offset {
         #[inline]
         pub fn $i(&self, offset: usize) -> Result<$t, EOFError> {
             let range = offset..offset.wrapping_add(mem::size_of::<$t>());
-            let data = self.untrusted_buffer.get(range).ok_or(EOFError)?;
+            let data = self.untrusted_buffer.get(range).ok_or_else(|| {
+                EOFError::new(
+                    offset.saturating_add(mem::size_of::<$t>()),
+                    self.untrusted_buffer.len(),
+                )
+            })?;
             let mut res: $t = 0;
             for i in 0..mem::size_of::<$t>() {
                 res = res << 8 | data[mem::size_of::<$t>() - 1 - i] as $t
@@ -95,15 +147,132 @@ impl<'a> Reader<'a> {
     /// Create a [`Reader`] from a slice of data.
     #[inline]
     pub fn new(untrusted_buffer: &'a [u8]) -> Self {
-        Self { untrusted_buffer }
+        Self {
+            untrusted_buffer,
+            offset: 0,
+        }
     }
 
-    /// Returns the length of the data
+    /// Create a [`Reader`] from a slice of data that starts at `base_offset`
+    /// in some larger input, such as a file.  [`Self::offset`] then starts at
+    /// `base_offset`, so offsets are relative to the start of the larger
+    /// input.  So that offsets cannot overflow, `base_offset` is reduced if
+    /// adding the length of `untrusted_buffer` to it would overflow a
+    /// [`usize`]; this never happens for a real input.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::Reader;
+    /// let mut reader = Reader::new_at(&[5, 6], 96);
+    /// assert_eq!(reader.offset(), 96);
+    /// reader.byte().unwrap();
+    /// assert_eq!(reader.offset(), 97);
+    /// ```
+    #[inline]
+    pub fn new_at(untrusted_buffer: &'a [u8], base_offset: usize) -> Self {
+        Self {
+            untrusted_buffer,
+            offset: core::cmp::min(base_offset, !0 - untrusted_buffer.len()),
+        }
+    }
+
+    /// Returns an independent [`Reader`] over the remaining data, with the
+    /// same offset.  This is the same as copying `self`, and takes constant
+    /// time.  Reading from the returned [`Reader`] does not advance `self`, so
+    /// a caller can try to parse the data one way and fall back to another if
+    /// that fails.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::Reader;
+    /// let mut reader = Reader::new(&[1, 2, 3]);
+    /// reader.byte().unwrap();
+    /// let mut attempt = reader.clone_remaining();
+    /// assert_eq!(attempt.be_u16().unwrap(), 0x203);
+    /// assert_eq!((reader.len(), reader.offset()), (2, 1));
+    /// assert_eq!(reader.byte().unwrap(), 2);
+    /// ```
+    #[inline]
+    pub fn clone_remaining(&self) -> Reader<'a> {
+        *self
+    }
+
+    /// Returns the length of the data that has not yet been consumed
     #[inline]
     pub fn len(&self) -> usize {
         self.untrusted_buffer.len()
     }
 
+    /// Returns the number of bytes consumed since the [`Reader`] was created.
+    /// Readers returned by [`Self::get_read`] and passed to the callback of
+    /// [`Self::read_bytes`] count from the start of the outer reader.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::Reader;
+    /// let mut reader = Reader::new(&[5, 6, 7]);
+    /// assert_eq!(reader.offset(), 0);
+    /// reader.get_bytes(2).unwrap();
+    /// assert_eq!(reader.offset(), 2);
+    /// assert_eq!(reader.len(), 1);
+    /// assert!(reader.get_bytes(2).is_err());
+    /// assert_eq!(reader.offset(), 2);
+    /// ```
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns a [`Reader`] over `range` of the data that has not yet been
+    /// consumed, without consuming anything.  The new reader’s
+    /// [`Self::offset`] starts at `self.offset() + range.start`, so offsets
+    /// read from it are still relative to the start of the original data.
+    /// Returns [`None`] if `range` is out of bounds.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::Reader;
+    /// let mut reader = Reader::new(&[5, 6, 7, 8]);
+    /// reader.byte().unwrap();
+    /// let mut sub = reader.sub_reader(1..3).unwrap();
+    /// assert_eq!(sub.offset(), 2);
+    /// assert_eq!(sub.get_bytes(2).unwrap(), &[7, 8]);
+    /// assert!(sub.is_empty());
+    /// assert!(reader.sub_reader(1..4).is_none());
+    /// assert_eq!(reader.len(), 3);
+    /// ```
+    pub fn sub_reader(&self, range: core::ops::Range<usize>) -> Option<Reader<'a>> {
+        let start = range.start;
+        Some(Reader {
+            untrusted_buffer: self.untrusted_buffer.get(range)?,
+            offset: self.offset + start,
+        })
+    }
+
+    /// Splits the data that has not yet been consumed into two [`Reader`]s: one
+    /// over the first `n` bytes, and one over the rest.  As with
+    /// [`Self::sub_reader`], offsets are still relative to the start of the
+    /// original data.  If fewer than `n` bytes are left,
+    /// [`super::Error::Truncated`] is returned.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, Error};
+    /// let (first, rest) = Reader::new(&[5, 6, 7]).split_at(1).unwrap();
+    /// assert_eq!(first.as_untrusted_slice(), &[5]);
+    /// assert_eq!((rest.offset(), rest.as_untrusted_slice()), (1, &[6, 7][..]));
+    /// assert_eq!(
+    ///     Reader::new(&[5]).split_at(2).unwrap_err(),
+    ///     Error::Truncated { needed: 2, available: 1 },
+    /// );
+    /// ```
+    pub fn split_at(mut self, n: usize) -> Result<(Reader<'a>, Reader<'a>), super::Error> {
+        let offset = self.offset;
+        let first = self.get_bytes(n)?;
+        Ok((
+            Reader {
+                untrusted_buffer: first,
+                offset,
+            },
+            self,
+        ))
+    }
+
     /// Returns [`true`] if and only if the buffer is empty.
     ///
     /// ```rust
@@ -124,9 +293,7 @@ impl<'a> Reader<'a> {
     /// ```
     #[inline]
     pub fn empty() -> Self {
-        Reader {
-            untrusted_buffer: &b""[..],
-        }
+        Self::new(&b""[..])
     }
 
     /// Read a single byte from the buffer.  Returns [`None`] if the buffer is
@@ -144,29 +311,65 @@ impl<'a> Reader<'a> {
             .split_first()
             .map(|(&s, untrusted_rest)| {
                 self.untrusted_buffer = untrusted_rest;
+                self.offset += 1;
                 s
             })
     }
 
+    /// Returns the next byte in the buffer without consuming it.  Returns
+    /// [`None`] if the buffer is empty.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::Reader;
+    /// assert!(Reader::empty().peek_byte().is_none());
+    /// let mut reader = Reader::new(&[5, 6][..]);
+    /// assert_eq!(reader.peek_byte(), Some(5));
+    /// assert_eq!(reader.peek_byte(), Some(5));
+    /// assert_eq!(reader.len(), 2);
+    /// ```
+    #[inline]
+    pub fn peek_byte(&self) -> Option<u8> {
+        self.untrusted_buffer.first().cloned()
+    }
+
+    /// Returns the next `len` bytes in the buffer without consuming them.
+    /// Returns [`None`] if fewer than `len` bytes are left.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::Reader;
+    /// let reader = Reader::new(&[5, 6, 7][..]);
+    /// assert_eq!(reader.peek_bytes(2), Some(&[5, 6][..]));
+    /// assert_eq!(reader.peek_bytes(3), Some(&[5, 6, 7][..]));
+    /// assert!(reader.peek_bytes(4).is_none());
+    /// assert_eq!(reader.len(), 3);
+    /// ```
+    #[inline]
+    pub fn peek_bytes(&self, len: usize) -> Option<&'a [u8]> {
+        self.untrusted_buffer.get(..len)
+    }
+
     /// Same as [`Self::maybe_byte`], but fails if the buffer is empty.
     ///
     /// ```rust
-    /// # use openpgp_parser::{Reader,EOFError};
-    /// assert_eq!(Reader::empty().byte().unwrap_err(), EOFError);
+    /// # use openpgp_parser::Reader;
+    /// assert_eq!(Reader::empty().byte().unwrap_err().needed(), 1);
     /// let mut nonempty_reader = Reader::new(&[5][..]);
     /// assert_eq!(nonempty_reader.byte(), Ok(5));
-    /// assert_eq!(nonempty_reader.byte().unwrap_err(), EOFError);
+    /// assert_eq!(nonempty_reader.byte().unwrap_err().available(), 0);
     /// ```
     pub fn byte(&mut self) -> Result<u8, EOFError> {
-        self.maybe_byte().ok_or(EOFError)
+        self.maybe_byte().ok_or(EOFError::new(1, 0))
     }
 
-    /// Gets a slice; this is less safe.
+    /// Gets the data that has not yet been consumed as a slice; this is less
+    /// safe.
     ///
     /// ```rust
     /// # use openpgp_parser::Reader;
-    /// let mut nonempty_reader = Reader::new(&[5]);
-    /// assert_eq!(nonempty_reader.as_untrusted_slice(), &[5]);
+    /// let mut nonempty_reader = Reader::new(&[5, 6]);
+    /// assert_eq!(nonempty_reader.as_untrusted_slice(), &[5, 6]);
+    /// nonempty_reader.byte().unwrap();
+    /// assert_eq!(nonempty_reader.as_untrusted_slice(), &[6]);
     /// ```
     #[inline]
     pub fn as_untrusted_slice(&self) -> &'a [u8] {
@@ -326,14 +529,68 @@ impl<'a> Reader<'a> {
     /// ```
     pub fn get_bytes(&mut self, len: usize) -> Result<&'a [u8], EOFError> {
         if len > self.untrusted_buffer.len() {
-            Err(EOFError)
+            Err(EOFError::new(len, self.untrusted_buffer.len()))
         } else {
             let (untrusted_buffer, untrusted_rest) = self.untrusted_buffer.split_at(len);
             self.untrusted_buffer = untrusted_rest;
+            self.offset += len;
             Ok(untrusted_buffer)
         }
     }
 
+    /// Skips `len` bytes.  If fewer bytes are left,
+    /// [`super::Error::Truncated`] is returned and nothing is consumed.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, Error};
+    /// let mut reader = Reader::new(&[50, 6, 3]);
+    /// reader.skip(2).unwrap();
+    /// assert_eq!(reader.skip(2).unwrap_err(), Error::Truncated { needed: 2, available: 1 });
+    /// assert_eq!(reader.byte().unwrap(), 3);
+    /// ```
+    pub fn skip(&mut self, len: usize) -> Result<(), super::Error> {
+        self.get_bytes(len)?;
+        Ok(())
+    }
+
+    /// Copies the next `buf.len()` bytes into `buf`, consuming them.  If fewer
+    /// bytes are left, [`super::Error::Truncated`] is returned and nothing is
+    /// consumed.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, Error};
+    /// let mut reader = Reader::new(&[50, 6, 3]);
+    /// let mut buf = [0u8; 2];
+    /// reader.read_exact_into(&mut buf).unwrap();
+    /// assert_eq!(buf, [50, 6]);
+    /// assert_eq!(
+    ///     reader.read_exact_into(&mut buf).unwrap_err(),
+    ///     Error::Truncated { needed: 2, available: 1 },
+    /// );
+    /// assert_eq!(reader.len(), 1);
+    /// ```
+    pub fn read_exact_into(&mut self, buf: &mut [u8]) -> Result<(), super::Error> {
+        buf.copy_from_slice(self.get_bytes(buf.len())?);
+        Ok(())
+    }
+
+    /// Reads an OpenPGP multiprecision integer (MPI): a big-endian 16-bit bit
+    /// count, followed by the minimal number of bytes needed to hold that many
+    /// bits.  The bytes of the MPI are returned.  If the bit count does not
+    /// match the position of the first set bit, [`super::Error::BadMPI`] is
+    /// returned; see [`super::signature::validate_mpi`].
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, Error};
+    /// let mut reader = Reader::new(&[0, 9, 1, 0xFF, 0, 9, 2, 0]);
+    /// assert_eq!(reader.mpi().unwrap(), &[1, 0xFF]);
+    /// assert_eq!(reader.mpi().unwrap_err(), Error::BadMPI);
+    /// assert_eq!(reader.len(), 4);
+    /// ```
+    pub fn mpi(&mut self) -> Result<&'a [u8], super::Error> {
+        super::signature::read_mpi(self)
+    }
+
     /// Reads `len` bytes of data, then calls `cb` with the result.  `cb` must use all of those
     /// bytes, otherwise `trailing_junk` is returned.
     pub fn read_bytes<T, U, V: Fn(&mut Self) -> Result<T, U>>(
@@ -346,11 +603,16 @@ impl<'a> Reader<'a> {
             Err(trailing_junk)
         } else {
             let (untrusted_buffer, untrusted_rest) = self.untrusted_buffer.split_at(len);
-            let retval = cb(&mut Self { untrusted_buffer })?;
-            if !untrusted_buffer.is_empty() {
+            let mut reader = Self {
+                untrusted_buffer,
+                offset: self.offset,
+            };
+            let retval = cb(&mut reader)?;
+            if !reader.is_empty() {
                 return Err(trailing_junk);
             }
             self.untrusted_buffer = untrusted_rest;
+            self.offset += len;
             Ok(retval)
         }
     }
@@ -374,7 +636,7 @@ impl<'a> Reader<'a> {
     /// assert!(reader.is_empty()); // reader has been changed
     /// ```
     pub fn read<T, U, V: FnOnce(&mut Self) -> Result<T, U>>(&mut self, cb: V) -> Result<T, U> {
-        let mut dup = self.clone();
+        let mut dup = *self;
         let retval = cb(&mut dup)?;
         *self = dup;
         Ok(retval)
@@ -403,10 +665,11 @@ impl<'a> Reader<'a> {
         &mut self,
         cb: V,
     ) -> Result<(Self, T), U> {
-        let mut dup = self.clone();
+        let mut dup = *self;
         let retval = cb(&mut dup)?;
         let ret_buf = Self {
             untrusted_buffer: &self.untrusted_buffer[..self.len() - dup.len()],
+            offset: self.offset,
         };
         *self = dup;
         Ok((ret_buf, retval))
@@ -420,35 +683,288 @@ impl<'a> Reader<'a> {
         trailing_junk: U,
         cb: V,
     ) -> Result<T, U> {
-        let mut reader = Self { untrusted_buffer };
+        let mut reader = Self::new(untrusted_buffer);
         let retval = cb(&mut reader)?;
         match reader.is_empty() {
             true => Ok(retval),
             false => Err(trailing_junk),
         }
     }
+
+    /// Same as [`Self::read_all`], except that the error if the callback does
+    /// not consume the whole buffer is [`super::Error::TrailingJunk`], with the
+    /// offset of the first byte that was not consumed.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Error, Reader};
+    /// assert_eq!(Reader::read_all_strict(&[1, 2], |r| r.byte().map_err(Error::from)), Err(Error::TrailingJunk { offset: 1 }));
+    /// assert_eq!(Reader::read_all_strict(&[1, 2], |r| r.be_u16().map_err(Error::from)), Ok(0x102));
+    /// ```
+    pub fn read_all_strict<T, V: FnOnce(&mut Self) -> Result<T, super::Error>>(
+        untrusted_buffer: &'a [u8],
+        cb: V,
+    ) -> Result<T, super::Error> {
+        let mut reader = Self::new(untrusted_buffer);
+        let retval = cb(&mut reader)?;
+        match reader.is_empty() {
+            true => Ok(retval),
+            false => Err(super::Error::TrailingJunk {
+                offset: reader.offset(),
+            }),
+        }
+    }
 }
 
 impl From<EOFError> for super::Error {
-    fn from(_: EOFError) -> super::Error {
-        super::Error::PrematureEOF
+    fn from(e: EOFError) -> super::Error {
+        super::Error::Truncated {
+            needed: e.needed,
+            available: e.available,
+        }
+    }
+}
+
+/// A reader for bit-packed untrusted data.  Bits are read most significant
+/// bit first.
+///
+/// ```rust
+/// # use openpgp_parser::{BitReader, Error};
+/// let mut reader = BitReader::new(&[0b1011_0010, 0xFF]);
+/// assert_eq!(reader.read_bits(1).unwrap(), 1);
+/// assert_eq!(reader.read_bits(3).unwrap(), 0b011);
+/// assert_eq!(reader.read_bits(8).unwrap(), 0b0010_1111);
+/// assert_eq!(reader.bits_remaining(), 4);
+/// assert_eq!(
+///     reader.read_bits(5).unwrap_err(),
+///     Error::Truncated { needed: 2, available: 1 },
+/// );
+/// assert_eq!(reader.read_bits(4).unwrap(), 0xF);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BitReader<'a> {
+    untrusted_buffer: &'a [u8],
+    bit_offset: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// Creates a [`BitReader`] over `untrusted_buffer`
+    pub fn new(untrusted_buffer: &'a [u8]) -> Self {
+        Self {
+            untrusted_buffer,
+            bit_offset: 0,
+        }
+    }
+
+    /// The number of bits that have not been read yet
+    pub fn bits_remaining(&self) -> u64 {
+        self.untrusted_buffer.len() as u64 * 8 - self.bit_offset as u64
+    }
+
+    /// Reads `n` bits as a big-endian integer.  If fewer than `n` bits are
+    /// left, [`super::Error::Truncated`] is returned and nothing is consumed.
+    /// Its byte counts include the partly read byte, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than 32.  This depends only on the caller,
+    /// never on the data.
+    pub fn read_bits(&mut self, n: u8) -> Result<u32, super::Error> {
+        assert!(n <= 32, "cannot read more than 32 bits at once");
+        if u64::from(n) > self.bits_remaining() {
+            return Err(super::Error::Truncated {
+                needed: 1 + (self.bit_offset % 8 + usize::from(n) - 1) / 8,
+                available: self.untrusted_buffer.len() - self.bit_offset / 8,
+            });
+        }
+        let mut value = 0u32;
+        for _ in 0..n {
+            let byte = self.untrusted_buffer[self.bit_offset / 8];
+            let bit = byte >> (7 - self.bit_offset % 8) & 1;
+            value = value << 1 | u32::from(bit);
+            self.bit_offset += 1;
+        }
+        Ok(value)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[test]
+    fn clone_remaining_is_independent() {
+        let data = [1u8, 2, 3, 4, 5];
+        let mut reader = Reader::new_at(&data, 10);
+        reader.byte().unwrap();
+        let saved = reader.clone_remaining();
+        let mut copy = reader;
+        assert_eq!(copy.be_u32().unwrap(), 0x0203_0405);
+        assert!(copy.is_empty());
+        assert_eq!(copy.offset(), 15);
+        assert_eq!(reader, saved);
+        assert_eq!((reader.len(), reader.offset()), (4, 11));
+        // a failed parse of the clone leaves the original usable
+        let mut attempt = reader.clone_remaining();
+        assert!(attempt.read(|r| r.get_bytes(5)).is_err());
+        attempt.skip(4).unwrap();
+        assert_eq!(reader.get_bytes(4).unwrap(), &data[1..]);
+        assert_eq!(reader.offset(), attempt.offset());
+    }
+
     #[test]
     fn read_one_byte() {
         let mut buffer = Reader::new(b"abc");
-        assert_eq!(buffer.get_bytes(4), Err(EOFError));
+        assert_eq!(buffer.get_bytes(4), Err(EOFError::new(4, 3)));
         assert_eq!(buffer.maybe_byte(), Some(b'a'));
         assert_eq!(buffer.get_bytes(2), Ok(&b"bc"[..]));
-        assert_eq!(buffer.get_bytes(2), Err(EOFError));
+        assert_eq!(buffer.get_bytes(2), Err(EOFError::new(2, 0)));
         assert!(buffer.maybe_byte().is_none());
         assert!(buffer.byte().is_err());
     }
 
+    #[test]
+    fn sub_reader() {
+        let data = [1, 2, 3, 4, 5];
+        let mut reader = Reader::new(&data);
+        reader.skip(2).unwrap();
+        let sub = reader.sub_reader(0..3).unwrap();
+        assert_eq!((sub.offset(), sub.as_untrusted_slice()), (2, &data[2..]));
+        let sub = reader.sub_reader(3..3).unwrap();
+        assert_eq!((sub.offset(), sub.len()), (5, 0));
+        assert!(reader.sub_reader(0..4).is_none());
+        assert!(reader.sub_reader(4..4).is_none());
+        #[allow(clippy::reversed_empty_ranges)]
+        let backwards = reader.sub_reader(2..1);
+        assert!(backwards.is_none());
+        // nested sub-readers keep counting from the original data
+        let mut sub = reader.sub_reader(1..3).unwrap();
+        sub.byte().unwrap();
+        assert_eq!(sub.sub_reader(0..1).unwrap().offset(), 4);
+    }
+
+    #[test]
+    fn new_at() {
+        let data = [1, 2, 3, 4, 5];
+        let mut reader = Reader::new_at(&data, 1000);
+        assert_eq!(reader.offset(), 1000);
+        reader.skip(2).unwrap();
+        assert_eq!(reader.offset(), 1002);
+        let (first, rest) = reader.split_at(1).unwrap();
+        assert_eq!((first.offset(), rest.offset()), (1002, 1003));
+        assert_eq!(rest.sub_reader(1..2).unwrap().offset(), 1004);
+        // offsets cannot overflow
+        let mut reader = Reader::new_at(&data, !0);
+        assert_eq!(reader.offset(), !0 - 5);
+        reader.skip(5).unwrap();
+        assert_eq!(reader.offset(), !0);
+        assert_eq!(Reader::new_at(&[], !0).offset(), !0);
+    }
+
+    #[test]
+    fn split_at() {
+        let data = [1, 2, 3, 4, 5];
+        let mut reader = Reader::new(&data);
+        reader.skip(1).unwrap();
+        for n in 0..5 {
+            let (mut first, rest) = reader.split_at(n).unwrap();
+            assert_eq!((first.offset(), first.len()), (1, n));
+            assert_eq!(
+                (rest.offset(), rest.as_untrusted_slice()),
+                (1 + n, &data[1 + n..])
+            );
+            assert_eq!(first.get_bytes(n).unwrap(), &data[1..1 + n]);
+        }
+        assert_eq!(
+            reader.split_at(5).unwrap_err(),
+            super::super::Error::Truncated {
+                needed: 5,
+                available: 4
+            }
+        );
+    }
+
+    #[test]
+    fn skip() {
+        let mut reader = Reader::new(b"abc");
+        assert_eq!(
+            reader.skip(4),
+            Err(super::super::Error::Truncated {
+                needed: 4,
+                available: 3
+            })
+        );
+        assert_eq!(reader.offset(), 0);
+        assert_eq!(reader.skip(0), Ok(()));
+        assert_eq!(reader.skip(1), Ok(()));
+        assert_eq!(reader.offset(), 1);
+        assert_eq!(reader.skip(2), Ok(()));
+        assert!(reader.is_empty());
+        assert_eq!(
+            reader.skip(1),
+            Err(super::super::Error::Truncated {
+                needed: 1,
+                available: 0
+            })
+        );
+        assert_eq!(reader.skip(0), Ok(()));
+    }
+
+    #[test]
+    fn be_integers() {
+        let data = [1, 2, 3, 4, 5, 6, 7, 8];
+        assert_eq!(Reader::new(&data[..2]).be_u16(), Ok(0x102));
+        assert_eq!(Reader::new(&data[..4]).be_u32(), Ok(0x1020304));
+        assert_eq!(Reader::new(&data[..]).be_u64(), Ok(0x102030405060708));
+        // nothing is consumed on failure
+        let mut reader = Reader::new(&data[..1]);
+        assert_eq!(reader.be_u16(), Err(EOFError::new(2, 1)));
+        assert_eq!(reader.len(), 1);
+        let mut reader = Reader::new(&data[..3]);
+        assert_eq!(reader.be_u32(), Err(EOFError::new(4, 3)));
+        assert_eq!(reader.len(), 3);
+        let mut reader = Reader::new(&data[..7]);
+        assert_eq!(reader.be_u64(), Err(EOFError::new(8, 7)));
+        assert_eq!(reader.len(), 7);
+        assert_eq!(reader.be_u32_offset(4), Err(EOFError::new(8, 7)));
+        assert_eq!(reader.le_u16_offset(!0), Err(EOFError::new(!0, 7)));
+        #[cfg(feature = "std")]
+        assert_eq!(
+            std::io::Error::from(EOFError::new(4, 3)).to_string(),
+            "Unexpected EOF: needed 4 bytes, but only 3 available"
+        );
+        assert_eq!(
+            super::super::Error::from(EOFError::new(1, 0)),
+            super::super::Error::Truncated {
+                needed: 1,
+                available: 0
+            }
+        );
+    }
+
+    #[test]
+    fn offset() {
+        let mut buffer = Reader::new(b"abcdef");
+        assert_eq!(buffer.byte(), Ok(b'a'));
+        assert_eq!(buffer.be_u16(), Ok(0x6263));
+        assert_eq!(buffer.offset(), 3);
+        buffer
+            .read_bytes(2, (), |b| {
+                assert_eq!(b.offset(), 3);
+                b.get_bytes(2).map(drop).map_err(drop)
+            })
+            .unwrap();
+        assert_eq!(buffer.offset(), 5);
+        // the callback must consume everything
+        assert!(buffer.read_bytes(1, (), |_| Ok(())).is_err());
+        assert_eq!(buffer.offset(), 5);
+        let (span, ()) = buffer
+            .get_read::<_, (), _>(|b| b.byte().map(drop).map_err(drop))
+            .unwrap();
+        assert_eq!(span.offset(), 5);
+        assert_eq!(span.as_untrusted_slice(), b"f");
+        assert_eq!(buffer.offset(), 6);
+        assert!(buffer.is_empty());
+    }
+
     #[test]
     fn read() {
         let mut buffer = Reader::new(b"a");
@@ -474,4 +990,36 @@ mod tests {
         assert!(buffer.maybe_byte().is_none());
         assert!(buffer.byte().is_err());
     }
+
+    #[test]
+    fn bit_reader() {
+        let data = [0x12, 0x34, 0x56, 0x78, 0x9A];
+        let mut reader = BitReader::new(&data);
+        assert_eq!(reader.read_bits(0), Ok(0));
+        assert_eq!(reader.read_bits(4), Ok(0x1));
+        // across a byte boundary
+        assert_eq!(reader.read_bits(8), Ok(0x23));
+        assert_eq!(
+            reader.read_bits(32),
+            Err(super::super::Error::Truncated {
+                needed: 5,
+                available: 4
+            })
+        );
+        assert_eq!(reader.bits_remaining(), 28);
+        assert_eq!(reader.read_bits(28), Ok(0x456789A));
+        assert_eq!(
+            reader.read_bits(1),
+            Err(super::super::Error::Truncated {
+                needed: 1,
+                available: 0
+            })
+        );
+        assert_eq!(reader.read_bits(0), Ok(0));
+        let mut reader = BitReader::new(&data);
+        assert_eq!(reader.read_bits(3), Ok(0));
+        assert_eq!(reader.read_bits(32), Ok(0x91A2B3C4));
+        assert_eq!(reader.read_bits(5), Ok(0x1A));
+        assert_eq!(BitReader::new(&[]).bits_remaining(), 0);
+    }
 }
@@ -0,0 +1,482 @@
+//! Grouping the packets of a keyring into certificates
+//!
+//! A keyring is a flat sequence of packets.  Each certificate in it starts
+//! with a primary key packet, followed by user IDs, subkeys, and the
+//! signatures binding them to the primary key.  The functions here only look
+//! at packet tags; use [`super::key`] and [`super::signature`] to parse the
+//! packets themselves.
+
+#[cfg(feature = "sha1")]
+use super::key::PublicKey;
+use super::packet::{Packet, PacketIter};
+#[cfg(feature = "sha1")]
+use super::signature::{Signature, SignatureType};
+use super::{Error, ParseOptions, Reader};
+extern crate alloc;
+extern crate core;
+use self::alloc::vec::Vec;
+
+const TAG_SIGNATURE: u8 = 2;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_MARKER: u8 = 10;
+const TAG_TRUST: u8 = 12;
+const TAG_USER_ID: u8 = 13;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+const TAG_USER_ATTRIBUTE: u8 = 17;
+
+/// A certificate: a primary key and the packets that follow it, up to the
+/// next primary key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cert<'a> {
+    /// All packets, starting with the primary key
+    packets: Vec<Packet<'a>>,
+}
+
+/// An iterator over the packets of a [`Cert`] that have a given tag, as
+/// returned by [`Cert::user_ids`], [`Cert::user_attributes`],
+/// [`Cert::subkeys`], and [`Cert::signatures`]
+#[derive(Clone, Debug)]
+pub struct CertPackets<'b, 'a: 'b> {
+    iter: core::slice::Iter<'b, Packet<'a>>,
+    tag: u8,
+}
+
+impl<'b, 'a: 'b> Iterator for CertPackets<'b, 'a> {
+    type Item = &'b Packet<'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let tag = self.tag;
+        self.iter.by_ref().find(|packet| packet.tag() == tag)
+    }
+}
+
+impl<'a> Cert<'a> {
+    /// The primary key packet
+    pub fn primary(&self) -> &Packet<'a> {
+        &self.packets[0]
+    }
+
+    /// All packets of the certificate, in order, starting with the primary key
+    pub fn packets(&self) -> &[Packet<'a>] {
+        &self.packets
+    }
+
+    fn with_tag<'b>(&'b self, tag: u8) -> CertPackets<'b, 'a> {
+        CertPackets {
+            iter: self.packets.iter(),
+            tag,
+        }
+    }
+
+    /// The user ID packets
+    pub fn user_ids<'b>(&'b self) -> CertPackets<'b, 'a> {
+        self.with_tag(TAG_USER_ID)
+    }
+
+    /// The user attribute packets.  Use
+    /// [`super::user_id::user_attribute_subpackets`] to parse them.
+    pub fn user_attributes<'b>(&'b self) -> CertPackets<'b, 'a> {
+        self.with_tag(TAG_USER_ATTRIBUTE)
+    }
+
+    /// The public subkey packets
+    pub fn subkeys<'b>(&'b self) -> CertPackets<'b, 'a> {
+        self.with_tag(TAG_PUBLIC_SUBKEY)
+    }
+
+    /// All signature packets, whatever they are over
+    pub fn signatures<'b>(&'b self) -> CertPackets<'b, 'a> {
+        self.with_tag(TAG_SIGNATURE)
+    }
+
+    /// Finds the self-signature that policy, such as key flags and the key
+    /// expiration time, should be taken from: the direct-key signature (type
+    /// 0x1F) or positive certification (type 0x13) issued by the primary key
+    /// with the latest creation time.  Signatures that are expired at `now`,
+    /// were created after `now`, have no creation time, or cannot be parsed
+    /// are skipped.  If several signatures have the same creation time, the
+    /// first one wins.  Returns [`None`] if there is no such signature, or if
+    /// the primary key cannot be parsed.
+    ///
+    /// Signatures are matched to the primary key by
+    /// [`Signature::issuer_key_id`], which may come from the unhashed area.
+    /// Nothing here checks a signature cryptographically, so the caller must
+    /// still verify the result, and should fall back to another signature if
+    /// that fails.
+    #[cfg(feature = "sha1")]
+    pub fn primary_self_signature(&self, now: u32) -> Option<Signature<'a>> {
+        let key_id = PublicKey::parse(self.primary()).ok()?.key_id();
+        let mut best: Option<(u32, Signature<'a>)> = None;
+        for packet in self.signatures() {
+            let sig = match Signature::parse(packet) {
+                Ok(sig) => sig,
+                Err(_) => continue,
+            };
+            match SignatureType::from_u8(sig.signature_type()) {
+                SignatureType::KeySig | SignatureType::PositiveCert => {}
+                _ => continue,
+            }
+            if sig.issuer_key_id() != Some(key_id) || sig.is_expired(now) {
+                continue;
+            }
+            let created = match sig.creation_time() {
+                Some(created) if created <= now => created,
+                _ => continue,
+            };
+            match best {
+                Some((latest, _)) if latest >= created => {}
+                _ => best = Some((created, sig)),
+            }
+        }
+        best.map(|(_, sig)| sig)
+    }
+
+    /// Serializes the certificate in a canonical form.  Every packet is
+    /// written in new format with the shortest length encoding (see
+    /// [`Packet::canonicalize`]), in a stable order: the primary key and the
+    /// packets that follow it, then each user ID or user attribute with the
+    /// packets that follow it, and then each subkey with the packets that
+    /// follow it.  Within each of these groups, packets keep their original
+    /// order, and so do the groups themselves.
+    ///
+    /// The output is a fixed point: passing it to [`split_certs`] and
+    /// serializing the result gives the same bytes.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, cert::split_certs};
+    /// // primary key, subkey, user ID, signature
+    /// let data = [0x98, 0, 0xCE, 0, 0xCD, 1, b'a', 0xC2, 0];
+    /// let certs = split_certs(&mut Reader::new(&data)).unwrap();
+    /// assert_eq!(certs[0].serialize(), &[0xC6, 0, 0xCD, 1, b'a', 0xC2, 0, 0xCE, 0]);
+    /// ```
+    pub fn serialize(&self) -> Vec<u8> {
+        // Split the packets into groups, each starting with a component, and
+        // sort them by the kind of component.  The sort is stable.
+        let mut groups: Vec<(u8, &[Packet<'a>])> = Vec::new();
+        let mut start = 0;
+        let mut kind = 0;
+        for (i, packet) in self.packets.iter().enumerate() {
+            let next_kind = match packet.tag() {
+                TAG_USER_ID | TAG_USER_ATTRIBUTE => 1,
+                TAG_PUBLIC_SUBKEY => 2,
+                _ => continue,
+            };
+            groups.push((kind, &self.packets[start..i]));
+            start = i;
+            kind = next_kind;
+        }
+        groups.push((kind, &self.packets[start..]));
+        groups.sort_by_key(|&(kind, _)| kind);
+        let len = self.packets.iter().map(Packet::serialized_len).sum();
+        let mut out = Vec::with_capacity(len);
+        for &(_, packets) in &groups {
+            for packet in packets {
+                packet.serialize_into(&mut out);
+            }
+        }
+        out
+    }
+}
+
+/// Splits the packets in `reader` into certificates.  A new certificate starts
+/// at each public key (tag 6) packet.  If the first packet is not a public
+/// key, [`Error::IllFormedKey`] is returned.  An empty reader results in an
+/// empty list.
+///
+/// Marker (tag 10) and trust (tag 12) packets, which GnuPG may include in
+/// exported keyrings, are skipped wherever they appear, and are not part of
+/// any certificate.
+///
+/// ```rust
+/// # use openpgp_parser::{Reader, cert::split_certs};
+/// let certs = split_certs(&mut Reader::new(&[0xC6, 0, 0xCD, 0, 0xC6, 0])).unwrap();
+/// assert_eq!(certs.len(), 2);
+/// assert_eq!(certs[0].user_ids().count(), 1);
+/// assert_eq!(certs[1].packets().len(), 1);
+/// // trust packets are ignored
+/// let certs = split_certs(&mut Reader::new(&[0xCC, 0, 0xC6, 0, 0xCC, 1, 0])).unwrap();
+/// assert_eq!(certs[0].packets().len(), 1);
+/// ```
+pub fn split_certs<'a>(reader: &mut Reader<'a>) -> Result<Vec<Cert<'a>>, Error> {
+    split_certs_with_options(reader, &ParseOptions::default())
+}
+
+/// Same as [`split_certs`], but with the given options.  User IDs are always
+/// stored as raw packets, so their bytes are preserved whatever they contain.
+/// If [`ParseOptions::lenient_utf8`] is `false`, a user ID that is not valid
+/// UTF-8 results in [`Error::InvalidUtf8`], so
+/// [`super::user_id::parse_user_id`] cannot fail on the user IDs of the
+/// returned certificates.  As with [`split_certs`], the reader is left
+/// unchanged on error.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, ParseOptions, Reader, cert::split_certs_with_options};
+/// let keyring = [0xC6, 0, 0xCD, 1, 0xE9];
+/// let certs = split_certs_with_options(&mut Reader::new(&keyring), &ParseOptions::default());
+/// assert_eq!(certs.unwrap()[0].user_ids().next().unwrap().contents(), &[0xE9]);
+/// let strict = ParseOptions { lenient_utf8: false, ..ParseOptions::default() };
+/// let certs = split_certs_with_options(&mut Reader::new(&keyring), &strict);
+/// assert_eq!(certs.unwrap_err(), Error::InvalidUtf8);
+/// ```
+pub fn split_certs_with_options<'a>(
+    reader: &mut Reader<'a>,
+    options: &ParseOptions,
+) -> Result<Vec<Cert<'a>>, Error> {
+    let mut certs: Vec<Cert<'a>> = Vec::new();
+    for packet in PacketIter::with_options(*reader, options) {
+        let packet = packet?;
+        let tag = packet.tag();
+        if tag == TAG_MARKER || tag == TAG_TRUST {
+            continue;
+        } else if tag == TAG_USER_ID && !options.lenient_utf8 {
+            super::user_id::parse_user_id(&packet)?;
+        }
+        if tag == TAG_PUBLIC_KEY {
+            certs.push(Cert {
+                packets: core::iter::once(packet).collect(),
+            })
+        } else {
+            match certs.last_mut() {
+                Some(cert) => cert.packets.push(packet),
+                None => return Err(Error::IllFormedKey),
+            }
+        }
+    }
+    let len = reader.len();
+    reader.skip(len)?;
+    Ok(certs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    static ED25519_KEY: &[u8] = include_bytes!("../../data/ed25519-key.gpg");
+
+    #[test]
+    fn splits_keyring() {
+        let mut keyring = ED25519_KEY.to_owned();
+        keyring.extend_from_slice(&ED25519_KEY[..82]);
+        let mut reader = Reader::new(&keyring);
+        let certs = split_certs(&mut reader).unwrap();
+        assert!(reader.is_empty());
+        assert_eq!(reader.offset(), keyring.len());
+        assert_eq!(certs.len(), 2);
+        let cert = &certs[0];
+        assert_eq!(cert.primary().contents(), &ED25519_KEY[2..53]);
+        assert_eq!(cert.packets().len(), 5);
+        let user_ids: Vec<_> = cert.user_ids().map(Packet::contents).collect();
+        assert_eq!(user_ids, [&b"Test Key <test@example.com>"[..]]);
+        let subkeys: Vec<_> = cert.subkeys().map(Packet::contents).collect();
+        assert_eq!(subkeys, [&ED25519_KEY[230..286]]);
+        assert_eq!(cert.signatures().count(), 2);
+        assert_eq!(certs[1].packets(), &cert.packets()[..2]);
+        assert_eq!(certs[1].subkeys().count(), 0);
+        assert_eq!(cert.user_attributes().count(), 0);
+        assert!(split_certs(&mut Reader::empty()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn skips_trust_and_marker_packets() {
+        let trust = [0xB0, 2, 0, 0];
+        let marker = [0xCA, 3, b'P', b'G', b'P'];
+        let mut keyring = marker.to_vec();
+        for &(start, end) in &[(0, 53), (53, 82), (82, 228), (228, 286), (286, 408)] {
+            keyring.extend_from_slice(&ED25519_KEY[start..end]);
+            keyring.extend_from_slice(&trust);
+        }
+        keyring.extend_from_slice(&marker);
+        keyring.extend_from_slice(&ED25519_KEY[..82]);
+        keyring.extend_from_slice(&trust);
+        let with_trust = split_certs(&mut Reader::new(&keyring)).unwrap();
+        let mut plain = ED25519_KEY.to_owned();
+        plain.extend_from_slice(&ED25519_KEY[..82]);
+        let without_trust = split_certs(&mut Reader::new(&plain)).unwrap();
+        assert_eq!(with_trust, without_trust);
+        assert_eq!(with_trust[0].packets().len(), 5);
+        assert_eq!(with_trust[1].packets().len(), 2);
+        // only trust packets
+        assert!(split_certs(&mut Reader::new(&trust)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn serialize_round_trip() {
+        let certs = split_certs(&mut Reader::new(ED25519_KEY)).unwrap();
+        let canonical = certs[0].serialize();
+        let reparsed = split_certs(&mut Reader::new(&canonical)).unwrap();
+        assert_eq!(reparsed.len(), 1);
+        assert_eq!(reparsed[0].serialize(), canonical);
+        fn contents<'a>(cert: &Cert<'a>) -> Vec<(u8, &'a [u8])> {
+            cert.packets()
+                .iter()
+                .map(|p| (p.tag(), p.contents()))
+                .collect()
+        }
+        assert_eq!(contents(&reparsed[0]), contents(&certs[0]));
+        // subkey groups move after user ID groups
+        let mut keyring = ED25519_KEY[..53].to_vec();
+        keyring.extend_from_slice(&ED25519_KEY[228..]);
+        keyring.extend_from_slice(&ED25519_KEY[53..228]);
+        let shuffled = split_certs(&mut Reader::new(&keyring)).unwrap();
+        assert_eq!(shuffled[0].serialize(), canonical);
+        // signatures directly over the primary key stay with it
+        let mut keyring = ED25519_KEY[..53].to_vec();
+        keyring.extend_from_slice(&ED25519_KEY[82..228]);
+        keyring.extend_from_slice(&ED25519_KEY[228..]);
+        keyring.extend_from_slice(&ED25519_KEY[53..82]);
+        let serialized = split_certs(&mut Reader::new(&keyring)).unwrap()[0].serialize();
+        let tags: Vec<_> = PacketIter::new(Reader::new(&serialized))
+            .map(|p| p.unwrap().tag())
+            .collect();
+        assert_eq!(tags, [6, 2, 13, 14, 2]);
+    }
+
+    #[test]
+    fn keeps_user_attributes() {
+        let attribute = [0xD1, 4, 3, 1, 0xAB, 0xCD];
+        let mut keyring = ED25519_KEY[..228].to_vec();
+        keyring.extend_from_slice(&attribute);
+        keyring.extend_from_slice(&ED25519_KEY[228..]);
+        let certs = split_certs(&mut Reader::new(&keyring)).unwrap();
+        assert_eq!(certs.len(), 1);
+        let attributes: Vec<_> = certs[0].user_attributes().collect();
+        assert_eq!(attributes.len(), 1);
+        let subpackets: Vec<_> = super::super::user_id::user_attribute_subpackets(attributes[0])
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(subpackets, [(1, &[0xAB, 0xCD][..])]);
+        assert_eq!(certs[0].subkeys().count(), 1);
+        // the attribute is a separate group, after the user ID's certification
+        let serialized = certs[0].serialize();
+        let tags: Vec<_> = PacketIter::new(Reader::new(&serialized))
+            .map(|p| p.unwrap().tag())
+            .collect();
+        assert_eq!(tags, [6, 13, 2, 17, 14, 2]);
+    }
+
+    /// Builds a signature packet issued by the test key, with subpackets for
+    /// the creation time and, if not zero, the expiration time
+    #[cfg(feature = "sha1")]
+    fn self_sig(sig_type: u8, created: u32, expires: u32, issuer: u8) -> Vec<u8> {
+        let mut hashed = vec![5, 2];
+        hashed.extend_from_slice(&created.to_be_bytes());
+        if expires != 0 {
+            hashed.extend_from_slice(&[5, 3]);
+            hashed.extend_from_slice(&expires.to_be_bytes());
+        }
+        let mut body = vec![4, sig_type, 22, 8, 0, hashed.len() as u8];
+        body.extend_from_slice(&hashed);
+        body.extend_from_slice(&[0, 10, 9, 16]);
+        body.extend_from_slice(&[0xE1, 0xB5, 0xE5, 0xB1, 0x30, 0xA4, 0xB4, issuer]);
+        body.extend_from_slice(&[0x61, 0x58, 0, 1, 1]);
+        Packet::new(2, &body).unwrap().serialize()
+    }
+
+    #[cfg(feature = "sha1")]
+    #[test]
+    fn primary_self_signature() {
+        let created = 1611619200;
+        let certs = split_certs(&mut Reader::new(ED25519_KEY)).unwrap();
+        let sig = certs[0].primary_self_signature(created).unwrap();
+        assert_eq!(sig.creation_time(), Some(created));
+        assert_eq!(sig.key_flags(), Some(0x03));
+        assert!(certs[0].primary_self_signature(created - 1).is_none());
+        let mut keyring = ED25519_KEY[..228].to_vec();
+        let sigs = [
+            // newer, but expires after a day
+            self_sig(0x13, created + 100, 86400, 0x61),
+            // a direct-key signature, newer still
+            self_sig(0x1F, created + 200, 0, 0x61),
+            // newest of all, but made by another key
+            self_sig(0x13, created + 300, 0, 0x62),
+            // newest of all, but not a self-signature
+            self_sig(0x10, created + 300, 0, 0x61),
+            self_sig(0x30, created + 300, 0, 0x61),
+            // the same time as the direct-key signature
+            self_sig(0x13, created + 200, 0, 0x61),
+        ];
+        for sig in &sigs {
+            keyring.extend_from_slice(sig);
+        }
+        keyring.extend_from_slice(&ED25519_KEY[228..]);
+        let certs = split_certs(&mut Reader::new(&keyring)).unwrap();
+        let cert = &certs[0];
+        let time_and_type = |now| {
+            cert.primary_self_signature(now)
+                .map(|sig| (sig.creation_time().unwrap() - created, sig.signature_type()))
+        };
+        assert_eq!(time_and_type(created - 1), None);
+        assert_eq!(time_and_type(created + 99), Some((0, 0x13)));
+        assert_eq!(time_and_type(created + 100), Some((100, 0x13)));
+        assert_eq!(time_and_type(created + 200), Some((200, 0x1F)));
+        assert_eq!(time_and_type(!0), Some((200, 0x1F)));
+        // once the direct-key and later signatures are gone, the expiring
+        // certification is only used until it expires
+        let mut keyring = ED25519_KEY[..228].to_vec();
+        keyring.extend_from_slice(&sigs[0]);
+        let certs = split_certs(&mut Reader::new(&keyring)).unwrap();
+        let sig = certs[0]
+            .primary_self_signature(created + 100 + 86399)
+            .unwrap();
+        assert_eq!(sig.creation_time(), Some(created + 100));
+        let sig = certs[0]
+            .primary_self_signature(created + 100 + 86400)
+            .unwrap();
+        assert_eq!(sig.creation_time(), Some(created));
+        // no self-signatures at all
+        let certs = split_certs(&mut Reader::new(&ED25519_KEY[..82])).unwrap();
+        assert!(certs[0].primary_self_signature(!0).is_none());
+    }
+
+    #[test]
+    fn non_utf8_user_ids() {
+        // A Latin-1 user ID between the real one and its self-signature
+        let latin1 = b"J\xFCrgen <j@example.com>";
+        let mut keyring = ED25519_KEY[..82].to_vec();
+        keyring.extend_from_slice(&Packet::new(TAG_USER_ID, latin1).unwrap().serialize());
+        keyring.extend_from_slice(&ED25519_KEY[82..]);
+        keyring.extend_from_slice(&ED25519_KEY[..53]);
+        let mut reader = Reader::new(&keyring);
+        let certs = split_certs(&mut reader).unwrap();
+        assert!(reader.is_empty());
+        assert_eq!(certs.len(), 2);
+        let user_ids: Vec<_> = certs[0].user_ids().map(Packet::contents).collect();
+        assert_eq!(user_ids, [&b"Test Key <test@example.com>"[..], &latin1[..]]);
+        assert_eq!(certs[0].subkeys().count(), 1);
+        assert_eq!(certs[0].signatures().count(), 2);
+        assert_eq!(certs[1].packets().len(), 1);
+        let lenient = ParseOptions::default();
+        assert_eq!(
+            split_certs_with_options(&mut Reader::new(&keyring), &lenient).unwrap(),
+            certs
+        );
+        let strict = ParseOptions {
+            lenient_utf8: false,
+            ..ParseOptions::default()
+        };
+        let mut reader = Reader::new(&keyring);
+        assert_eq!(
+            split_certs_with_options(&mut reader, &strict),
+            Err(Error::InvalidUtf8)
+        );
+        assert_eq!(reader.len(), keyring.len());
+        let certs = split_certs_with_options(&mut Reader::new(ED25519_KEY), &strict).unwrap();
+        assert_eq!(certs[0].user_ids().count(), 1);
+    }
+
+    #[test]
+    fn rejects_bad_keyrings() {
+        // starts with a user ID
+        let mut reader = Reader::new(&ED25519_KEY[53..]);
+        assert_eq!(split_certs(&mut reader).unwrap_err(), Error::IllFormedKey);
+        assert_eq!(reader.len(), ED25519_KEY.len() - 53);
+        // truncated
+        let mut reader = Reader::new(&ED25519_KEY[..100]);
+        assert_eq!(
+            split_certs(&mut reader).unwrap_err(),
+            Error::Truncated {
+                needed: 144,
+                available: 16
+            }
+        );
+        assert_eq!(reader.len(), 100);
+    }
+}
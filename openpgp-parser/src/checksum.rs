@@ -0,0 +1,40 @@
+//! Checksums used by OpenPGP
+
+/// Computes the CRC-24 checksum defined in RFC 4880 section 6.1, which is used
+/// by ASCII armor.  The generator is 0x864CFB and the initial value is
+/// 0xB704CE.  Only the low 24 bits of the result are ever set.
+///
+/// ```rust
+/// # use openpgp_parser::checksum::crc24;
+/// assert_eq!(crc24(b""), 0xB704CE);
+/// assert_eq!(crc24(b"123456789"), 0x21CF02);
+/// ```
+pub fn crc24(data: &[u8]) -> u32 {
+    let mut crc = 0xB704CEu32;
+    for &byte in data {
+        crc ^= u32::from(byte) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= 0x1864CFB;
+            }
+        }
+    }
+    crc & 0xFFFFFF
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn rfc4880_armor_example() {
+        // The body of the example message in RFC 4880 section 6.6, whose
+        // checksum is `=njUN`
+        let data = b"\xc8\x38\x01\x3b\x6d\x96\xc4\x11\xef\xec\xef\x17\xec\xef\xe3\xca\x00\x04\
+                     \xce\x89\x79\xea\x25\x0a\x89\x79\x95\xf9\x79\xa9\x0a\xd9\xa9\xa9\x05\x0a\
+                     \x89\x0a\xc5\xa9\xc9\x45\xa9\x40\xc1\xa2\xfc\xd2\xbc\x14\x85\x8c\xd4\xa2\
+                     \x54\x7b\x2e\x00";
+        assert_eq!(data.len(), 58);
+        assert_eq!(crc24(data), 0x9E350D);
+    }
+}
@@ -0,0 +1,121 @@
+//! Hashing data while it is parsed
+//!
+//! `openpgp-parser` has no cryptography of its own, so the hash function is
+//! provided by the caller through the [`Digest`] trait.  [`HashingReader`]
+//! feeds every byte consumed from a [`Reader`] into the hash, so that data
+//! such as an RPM header can be parsed and hashed in a single pass.
+
+use super::Reader;
+
+/// A hash function that can be fed data incrementally
+pub trait Digest {
+    /// The type of the final hash value
+    type Output;
+    /// Adds `data` to the hash
+    fn update(&mut self, data: &[u8]);
+    /// Consumes the hash state, returning the hash of all data added so far
+    fn finalize(self) -> Self::Output;
+}
+
+/// A [`Reader`] that hashes the data read from it
+///
+/// ```rust
+/// # use openpgp_parser::{Error, Reader, hashing::{Digest, HashingReader}};
+/// struct Sum(u32);
+/// impl Digest for Sum {
+///     type Output = u32;
+///     fn update(&mut self, data: &[u8]) {
+///         self.0 += data.iter().map(|&b| u32::from(b)).sum::<u32>();
+///     }
+///     fn finalize(self) -> u32 {
+///         self.0
+///     }
+/// }
+/// let mut reader = HashingReader::new(Reader::new(&[1, 2, 3, 4]), Sum(0));
+/// assert_eq!(reader.read(|r| r.be_u16()), Ok(0x102));
+/// // nothing is hashed if the callback fails
+/// assert_eq!(reader.read(|r| r.get_bytes(3).map(drop)).unwrap_err().needed(), 3);
+/// assert_eq!(reader.read(|r| r.byte()), Ok(3));
+/// assert_eq!(reader.finalize(), 6);
+/// ```
+#[derive(Clone, Debug)]
+pub struct HashingReader<'a, H> {
+    reader: Reader<'a>,
+    hasher: H,
+}
+
+impl<'a, H: Digest> HashingReader<'a, H> {
+    /// Creates a [`HashingReader`] that reads from `reader` and hashes with
+    /// `hasher`
+    pub fn new(reader: Reader<'a>, hasher: H) -> Self {
+        Self { reader, hasher }
+    }
+
+    /// Calls `cb` with the underlying [`Reader`].  If `cb` succeeds, the data
+    /// it consumed is hashed.  If it fails, the [`Reader`] is left unchanged
+    /// and nothing is hashed.
+    pub fn read<T, U, V: FnOnce(&mut Reader<'a>) -> Result<T, U>>(
+        &mut self,
+        cb: V,
+    ) -> Result<T, U> {
+        let (consumed, retval) = self.reader.get_read(cb)?;
+        self.hasher.update(consumed.as_untrusted_slice());
+        Ok(retval)
+    }
+
+    /// The data that has not been read yet
+    pub fn remaining(&self) -> &Reader<'a> {
+        &self.reader
+    }
+
+    /// Returns the hash of all data that has been read
+    pub fn finalize(self) -> H::Output {
+        self.hasher.finalize()
+    }
+
+    /// Returns the hash state, for adding more data before finalizing
+    pub fn into_hasher(self) -> H {
+        self.hasher
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rpm::Header;
+    use super::*;
+
+    /// A "hash" that just collects the data
+    struct Collect(Vec<u8>);
+    impl Digest for Collect {
+        type Output = Vec<u8>;
+        fn update(&mut self, data: &[u8]) {
+            self.0.extend_from_slice(data)
+        }
+        fn finalize(self) -> Vec<u8> {
+            self.0
+        }
+    }
+
+    #[test]
+    fn hashes_rpm_header() {
+        static RPM: &[u8] = include_bytes!("../../data/lua-5.4.2-1.fc33.x86_64.rpm");
+        let mut reader = HashingReader::new(Reader::new(&RPM[96..]), Collect(Vec::new()));
+        let header = reader.read(Header::parse).unwrap();
+        assert_eq!(header.entry_count(), 9);
+        let len = 16 + 9 * 16 + 4244;
+        assert_eq!(reader.remaining().as_untrusted_slice(), &RPM[96 + len..]);
+        assert_eq!(reader.finalize(), &RPM[96..96 + len]);
+    }
+
+    #[cfg(feature = "sha1")]
+    #[test]
+    fn matches_direct_hash() {
+        use super::super::sha1::Sha1;
+        static KEY: &[u8] = include_bytes!("../../data/ed25519-key.gpg");
+        let mut reader = HashingReader::new(Reader::new(KEY), Sha1::new());
+        while reader.read(super::super::packet::next).unwrap().is_some() {}
+        let mut direct = Sha1::new();
+        direct.update(KEY);
+        assert_eq!(reader.finalize(), direct.finalize());
+    }
+}
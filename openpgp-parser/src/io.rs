@@ -0,0 +1,323 @@
+//! Reading packets from a [`std::io::Read`] implementation
+//!
+//! [`IoReader`] accepts exactly the same input as [`super::packet::next`], but
+//! reads it from a stream, so the whole input does not need to be in memory.
+//! Errors are reported as [`std::io::Error`]s; parse errors can be retrieved
+//! with [`std::io::Error::get_ref`].
+//!
+//! With the `zeroize` feature, the internal buffer of an [`IoReader`] is
+//! cleared with [`super::util::zeroize`] when the reader is dropped, and so
+//! are the previous contents of a buffer passed to [`IoReader::next_packet`].
+//! Data that the caller copies elsewhere, or that is left behind when a
+//! [`Vec`] grows, is not cleared.
+
+extern crate std;
+use self::std::io::{self, BufRead, Read};
+use self::std::vec::Vec;
+use super::packet::{self, Packet};
+use super::{EOFError, Error, Reader};
+
+impl From<Error> for io::Error {
+    fn from(e: Error) -> Self {
+        let kind = match e {
+            Error::PrematureEOF | Error::Truncated { .. } => io::ErrorKind::UnexpectedEof,
+            _ => io::ErrorKind::InvalidData,
+        };
+        io::Error::new(kind, e)
+    }
+}
+
+/// The size of the internal buffer of an [`IoReader`]
+const BUFFER_SIZE: usize = 8192;
+
+/// A minimal replacement for [`std::io::BufReader`], which can clear its
+/// buffer when it is dropped
+#[derive(Debug)]
+struct Buffered<R> {
+    inner: R,
+    buf: Vec<u8>,
+    pos: usize,
+    filled: usize,
+}
+
+impl<R: Read> Read for Buffered<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        // Large reads bypass the buffer entirely
+        if self.pos == self.filled && out.len() >= self.buf.len() {
+            return self.inner.read(out);
+        }
+        let len = {
+            let available = self.fill_buf()?;
+            let len = available.len().min(out.len());
+            out[..len].copy_from_slice(&available[..len]);
+            len
+        };
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: Read> BufRead for Buffered<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos == self.filled {
+            self.filled = self.inner.read(&mut self.buf)?;
+            self.pos = 0;
+        }
+        Ok(&self.buf[self.pos..self.filled])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.filled)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<R> Drop for Buffered<R> {
+    fn drop(&mut self) {
+        super::util::zeroize(&mut self.buf)
+    }
+}
+
+/// A buffered reader for untrusted data from a stream.  This provides the
+/// same basic operations as [`Reader`], except that data is returned in owned
+/// buffers.
+///
+/// Unlike [`Reader`], an [`IoReader`] cannot go back: data that has been read
+/// is consumed even if the operation fails.
+///
+/// ```rust
+/// # use openpgp_parser::io::IoReader;
+/// let mut reader = IoReader::new(&[5, 6, 7, 8, 9, 10][..]);
+/// assert_eq!(reader.byte().unwrap(), 5);
+/// assert_eq!(reader.be_u32().unwrap(), 0x6070809);
+/// assert!(reader.get_bytes(2).is_err());
+/// assert!(reader.maybe_byte().unwrap().is_none());
+/// ```
+#[derive(Debug)]
+pub struct IoReader<R> {
+    inner: Buffered<R>,
+}
+
+impl<R: Read> IoReader<R> {
+    /// Creates an [`IoReader`] that reads from `inner`
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner: Buffered {
+                inner,
+                buf: vec![0; BUFFER_SIZE],
+                pos: 0,
+                filled: 0,
+            },
+        }
+    }
+
+    /// Reads a single byte.  Returns [`None`] at end of file.
+    pub fn maybe_byte(&mut self) -> io::Result<Option<u8>> {
+        let byte = loop {
+            match self.inner.fill_buf() {
+                Ok(buf) => break buf.first().cloned(),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        };
+        if byte.is_some() {
+            self.inner.consume(1)
+        }
+        Ok(byte)
+    }
+
+    /// Same as [`Self::maybe_byte`], but fails at end of file
+    pub fn byte(&mut self) -> io::Result<u8> {
+        self.maybe_byte()?.ok_or_else(|| EOFError::new(1, 0).into())
+    }
+
+    /// Reads a big-endian `u16` value
+    pub fn be_u16(&mut self) -> io::Result<u16> {
+        let mut buf = [0u8; 2];
+        self.inner.read_exact(&mut buf)?;
+        Ok(u16::from(buf[0]) << 8 | u16::from(buf[1]))
+    }
+
+    /// Reads a big-endian `u32` value
+    pub fn be_u32(&mut self) -> io::Result<u32> {
+        let mut buf = [0u8; 4];
+        self.inner.read_exact(&mut buf)?;
+        Ok(buf.iter().fold(0, |acc, &b| acc << 8 | u32::from(b)))
+    }
+
+    /// Reads `len` bytes.  Memory is allocated as data arrives, so a bogus
+    /// length does not cause a huge allocation by itself.
+    pub fn get_bytes(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        self.read_into(len, &mut buf)?;
+        Ok(buf)
+    }
+
+    fn read_into(&mut self, len: usize, buf: &mut Vec<u8>) -> io::Result<()> {
+        #[cfg(feature = "zeroize")]
+        super::util::zeroize(buf);
+        buf.clear();
+        self.inner.by_ref().take(len as u64).read_to_end(buf)?;
+        if buf.len() == len {
+            Ok(())
+        } else {
+            Err(EOFError::new(len, buf.len()).into())
+        }
+    }
+
+    /// Reads a packet, storing its body in `body`.  Any previous contents of
+    /// `body` are discarded.  Returns:
+    ///
+    /// - `Ok(Some(packet))` if a packet is read
+    /// - `Ok(None)` at end of file
+    /// - `Err` if an I/O error occurred or the packet is invalid.  For invalid
+    ///   packets, the error wraps the same [`Error`] that
+    ///   [`super::packet::next`] would return.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Error, io::IoReader};
+    /// let mut reader = IoReader::new(&[0xC2, 1, 5, 0xC0, 0][..]);
+    /// let mut body = Vec::new();
+    /// assert_eq!(reader.next_packet(&mut body).unwrap().unwrap().contents(), &[5]);
+    /// let err = reader.next_packet(&mut body).unwrap_err();
+    /// assert_eq!(err.get_ref().unwrap().downcast_ref(), Some(&Error::BadTag(0xC0)));
+    /// ```
+    pub fn next_packet<'b>(&mut self, body: &'b mut Vec<u8>) -> io::Result<Option<Packet<'b>>> {
+        // At most 6 bytes: a tag byte, then either an old-format length of up
+        // to 4 bytes or a new-format length of up to 5 bytes.  The header is
+        // grown one byte at a time until the slice-based parser accepts it.
+        let mut header = [0u8; 6];
+        let mut header_len = 0;
+        let (tagbyte, tag, format, len) = loop {
+            match packet::read_header(&mut Reader::new(&header[..header_len])) {
+                Ok(Some(header)) => break header,
+                Ok(None) | Err(Error::PrematureEOF) | Err(Error::Truncated { .. }) => {}
+                Err(e) => return Err(e.into()),
+            }
+            match self.maybe_byte()? {
+                Some(byte) => header[header_len] = byte,
+                None if header_len == 0 => return Ok(None),
+                None => return Err(EOFError::new(header_len + 1, header_len).into()),
+            }
+            header_len += 1;
+        };
+        self.read_into(len, body)?;
+        if tag == 0 {
+            return Err(Error::BadTag(tagbyte).into());
+        }
+        Ok(Some(Packet {
+            tag,
+            format,
+            buffer: &body[..],
+            raw: None,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parses `data` with both [`packet::next`] and [`IoReader::next_packet`],
+    /// checking that the results are the same.
+    fn check_same(data: &[u8]) {
+        let mut reader = Reader::new(data);
+        let mut io_reader = IoReader::new(data);
+        let mut body = Vec::new();
+        loop {
+            match (packet::next(&mut reader), io_reader.next_packet(&mut body)) {
+                (Ok(Some(expected)), Ok(Some(actual))) => {
+                    assert_eq!(expected.tag(), actual.tag());
+                    assert_eq!(expected.format(), actual.format());
+                    assert_eq!(expected.contents(), actual.contents());
+                }
+                (Ok(None), Ok(None)) => break,
+                (Err(expected), Err(actual)) => {
+                    assert_eq!(io::Error::from(expected).kind(), actual.kind());
+                    match expected {
+                        Error::PrematureEOF | Error::Truncated { .. } => {}
+                        _ => assert_eq!(actual.get_ref().unwrap().downcast_ref(), Some(&expected)),
+                    }
+                    break;
+                }
+                (expected, actual) => panic!("{:?} != {:?}", expected, actual),
+            }
+        }
+    }
+
+    /// A reader that returns at most one byte per call
+    struct Trickle(&'static [u8]);
+
+    impl Read for Trickle {
+        fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+            match (self.0.split_first(), out.first_mut()) {
+                (Some((&byte, rest)), Some(first)) => {
+                    *first = byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn buffering() {
+        static KEY: &[u8] = include_bytes!("../../data/ed25519-key.gpg");
+        fn check<R: Read>(mut reader: IoReader<R>) {
+            let mut body = Vec::new();
+            let mut packets = packet::PacketIter::new(Reader::new(KEY));
+            while let Some(packet) = reader.next_packet(&mut body).unwrap() {
+                assert_eq!(
+                    packet.contents(),
+                    packets.next().unwrap().unwrap().contents()
+                );
+            }
+            assert!(packets.next().is_none());
+        }
+        check(IoReader::new(KEY));
+        check(IoReader::new(Trickle(KEY)));
+        // reads larger than the buffer
+        let data: Vec<u8> = (0..3 * BUFFER_SIZE).map(|i| i as u8).collect();
+        let mut reader = IoReader::new(&data[..]);
+        assert_eq!(reader.byte().unwrap(), 0);
+        assert_eq!(
+            reader.get_bytes(2 * BUFFER_SIZE).unwrap(),
+            &data[1..][..2 * BUFFER_SIZE]
+        );
+        assert_eq!(
+            reader.get_bytes(BUFFER_SIZE - 1).unwrap(),
+            &data[2 * BUFFER_SIZE + 1..]
+        );
+        assert!(reader.maybe_byte().unwrap().is_none());
+    }
+
+    #[test]
+    fn same_as_slice_reader() {
+        let key = include_bytes!("../../data/ed25519-key.gpg");
+        let sig = include_bytes!("../../data/eddsa.asc");
+        for data in &[&key[..], &sig[..]] {
+            for len in 0..=data.len() {
+                check_same(&data[..len]);
+            }
+        }
+        for data in &[
+            &[0x00][..],
+            &[0x80, 0],
+            &[0x88, 0],
+            &[0x89, 0, 1, 5],
+            &[0x8A, 0, 0, 0, 1, 5],
+            &[0x8B, 5],
+            &[0xC0, 0],
+            &[0xC2, 0xC0, 0],
+            &[0xC2, 0xFF, 0, 0, 0, 1, 5],
+            &[0xC2, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF],
+            &[0xCB, 0xE0, 5],
+        ] {
+            check_same(data);
+        }
+        let mut long = vec![0xC2, 0xDF, 0xFF];
+        long.resize(3 + 8383, 0);
+        check_same(&long);
+    }
+}
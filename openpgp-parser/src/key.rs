@@ -0,0 +1,463 @@
+//! OpenPGP public keys
+
+use super::algorithm::PublicKeyAlgorithm;
+use super::signature::Signature;
+use super::{packet, Error, Reader};
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "sha1")]
+use sha1::Sha1;
+
+/// A version 4 public key or public subkey packet
+#[derive(Clone, Debug)]
+pub struct PublicKey<'a> {
+    body: &'a [u8],
+    creation_time: u32,
+    pkey_alg: u8,
+    curve_oid: Option<&'a [u8]>,
+    key_material: &'a [u8],
+}
+
+/// Reads the curve OID of an elliptic-curve key
+fn read_curve_oid<'a>(reader: &mut Reader<'a>) -> Result<&'a [u8], Error> {
+    match reader.byte()? {
+        // 0 and 0xFF are reserved for future extensions
+        0 | 0xFF => Err(Error::IllFormedKey),
+        len => Ok(reader.get_bytes(len.into())?),
+    }
+}
+
+impl<'a> PublicKey<'a> {
+    /// Parses a public key (tag 6) or public subkey (tag 14) packet.  Only
+    /// version 4 keys are supported; other versions result in
+    /// [`Error::UnsupportedKeyVersion`].  The algorithm-specific key material
+    /// must be well-formed:
+    ///
+    /// - RSA: 2 MPIs (the modulus and the exponent)
+    /// - DSA: 4 MPIs
+    /// - Encrypt-only ElGamal: 3 MPIs
+    /// - ECDSA and EdDSA: a curve OID and 1 MPI (the point)
+    /// - ECDH: a curve OID, 1 MPI (the point), and the KDF parameters
+    ///
+    /// Other algorithms, including the withdrawn sign-and-encrypt ElGamal,
+    /// result in [`Error::UnsupportedPkeyAlgorithm`].  Encryption keys are
+    /// parsed so that the subkeys of a certificate can be checked, but nothing
+    /// in this crate uses them.
+    pub fn parse(packet: &packet::Packet<'a>) -> Result<Self, Error> {
+        match packet.tag() {
+            6 | 14 => {}
+            _ => return Err(Error::IllFormedKey),
+        }
+        let body = packet.contents();
+        Reader::read_all_strict(body, |reader| {
+            if reader.byte()? != 4 {
+                return Err(Error::UnsupportedKeyVersion);
+            }
+            let creation_time = reader.be_u32()?;
+            let pkey_alg = reader.byte()?;
+            let mut curve_oid = None;
+            let (key_material, ()) = reader.get_read(|reader| {
+                let mpis = match PublicKeyAlgorithm::from_u8(pkey_alg) {
+                    PublicKeyAlgorithm::Rsa
+                    | PublicKeyAlgorithm::RsaEncrypt
+                    | PublicKeyAlgorithm::RsaSign => 2,
+                    PublicKeyAlgorithm::Dsa => 4,
+                    PublicKeyAlgorithm::ElGamalEncrypt => 3,
+                    PublicKeyAlgorithm::Ecdsa | PublicKeyAlgorithm::EdDsa => {
+                        curve_oid = Some(read_curve_oid(reader)?);
+                        1
+                    }
+                    PublicKeyAlgorithm::Ecdh => {
+                        curve_oid = Some(read_curve_oid(reader)?);
+                        reader.mpi()?;
+                        // KDF parameters: length, reserved byte, hash
+                        // algorithm, and symmetric algorithm
+                        if reader.get_bytes(2)? != [3, 1] {
+                            return Err(Error::IllFormedKey);
+                        }
+                        reader.get_bytes(2)?;
+                        0
+                    }
+                    _ => return Err(Error::UnsupportedPkeyAlgorithm(pkey_alg)),
+                };
+                for _ in 0..mpis {
+                    reader.mpi()?;
+                }
+                Ok(())
+            })?;
+            Ok(PublicKey {
+                body,
+                creation_time,
+                pkey_alg,
+                curve_oid,
+                key_material: key_material.as_untrusted_slice(),
+            })
+        })
+    }
+
+    /// The raw public-key algorithm
+    pub fn algorithm(&self) -> u8 {
+        self.pkey_alg
+    }
+
+    /// The creation time of the key, in seconds since the Unix epoch.  As with
+    /// [`super::signature::Signature::creation_time`], this is unsigned and
+    /// should not be converted to `i32`.
+    pub fn creation_time(&self) -> u32 {
+        self.creation_time
+    }
+
+    /// Checks whether the key has expired at time `now`, according to the key
+    /// expiration time in `self_sig`.  Returns [`None`] if `self_sig` does not
+    /// set an expiration time, or sets it to 0, as the key then never expires.
+    /// An expiration time past the end of the 32-bit timestamp range is
+    /// treated as never being reached.
+    ///
+    /// This does not check that `self_sig` is a valid self-signature over
+    /// this key; that is the caller's responsibility.
+    pub fn is_expired(&self, self_sig: &Signature, now: u32) -> Option<bool> {
+        match self_sig.key_expiration() {
+            None | Some(0) => None,
+            Some(expiration) => Some(match self.creation_time.checked_add(expiration) {
+                Some(expires_at) => now >= expires_at,
+                None => false,
+            }),
+        }
+    }
+
+    /// The OID of the curve used by an elliptic-curve key
+    pub fn curve_oid(&self) -> Option<&'a [u8]> {
+        self.curve_oid
+    }
+
+    /// The algorithm-specific key material: the MPIs of the key, preceded by
+    /// the curve OID for elliptic-curve keys.  For RSA keys, this is the
+    /// modulus `n` followed by the exponent `e`.  For ECDH keys, the KDF
+    /// parameters follow the point.
+    pub fn mpis(&self) -> &'a [u8] {
+        self.key_material
+    }
+
+    /// The body of the key packet
+    pub fn contents(&self) -> &'a [u8] {
+        self.body
+    }
+
+    /// The byte 0x99 followed by the 2-byte big-endian length of the key
+    /// packet body, which come before the body when it is hashed
+    #[cfg(any(feature = "alloc", feature = "sha1"))]
+    fn hash_header(&self) -> [u8; 3] {
+        let len = self.body.len();
+        assert!(len <= 0xFFFF, "key packet too long to hash");
+        [0x99, (len >> 8) as u8, len as u8]
+    }
+
+    /// The key as it is hashed: the byte 0x99, the 2-byte big-endian length of
+    /// the key packet body, and the body itself.  The SHA-1 hash of this is
+    /// the fingerprint of the key, and signatures over the key hash it the
+    /// same way.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key packet body is longer than 65535 bytes.
+    #[cfg(feature = "alloc")]
+    pub fn to_hashable(&self) -> alloc::vec::Vec<u8> {
+        let mut hashable = alloc::vec::Vec::with_capacity(3 + self.body.len());
+        hashable.extend_from_slice(&self.hash_header());
+        hashable.extend_from_slice(self.body);
+        hashable
+    }
+
+    /// The version 4 fingerprint of the key: the SHA-1 hash of
+    /// [`Self::to_hashable`].
+    #[cfg(feature = "sha1")]
+    pub fn fingerprint(&self) -> [u8; 20] {
+        let mut ctx = Sha1::new();
+        ctx.update(&self.hash_header());
+        ctx.update(self.body);
+        ctx.finalize()
+    }
+
+    /// The key ID of the key: the low 8 bytes of its fingerprint
+    #[cfg(feature = "sha1")]
+    pub fn key_id(&self) -> [u8; 8] {
+        let mut key_id = [0u8; 8];
+        key_id.copy_from_slice(&self.fingerprint()[12..]);
+        key_id
+    }
+}
+
+/// The data hashed, before the signature's own
+/// [`super::signature::Signature::hashed_material`] and trailer, by a
+/// version 4 certification of `uid` by `key`.  In order, this is:
+///
+/// 1. [`PublicKey::to_hashable`]: the byte 0x99, the 2-byte big-endian length
+///    of the key packet body, and the body itself.
+/// 2. The byte 0xB4 (0xD1 if `uid` is a user attribute packet).
+/// 3. The length of the body of `uid`, as a big-endian 32-bit number.
+/// 4. The body of `uid`.
+///
+/// Version 3 certifications hash the user ID without steps 2 and 3, and
+/// are not supported by this function.
+///
+/// # Panics
+///
+/// Panics if the key packet body is longer than 65535 bytes, or the body
+/// of `uid` is longer than `u32::MAX` bytes.
+#[cfg(feature = "alloc")]
+pub fn user_id_binding_input(key: &PublicKey, uid: &packet::Packet) -> alloc::vec::Vec<u8> {
+    let body = uid.contents();
+    let len = body.len();
+    assert!(len as u64 <= 0xFFFF_FFFF, "user ID packet too long to hash");
+    let mut hashable = key.to_hashable();
+    hashable.reserve(5 + len);
+    hashable.extend_from_slice(&[
+        if uid.tag() == 17 { 0xD1 } else { 0xB4 },
+        (len >> 24) as u8,
+        (len >> 16) as u8,
+        (len >> 8) as u8,
+        len as u8,
+    ]);
+    hashable.extend_from_slice(body);
+    hashable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    static ED25519_KEY: &[u8] = include_bytes!("../../data/ed25519-key.gpg");
+    #[cfg(feature = "alloc")]
+    static SHA1_CERT_KEY: &[u8] = include_bytes!("../../data/sha1-cert-key.gpg");
+
+    #[test]
+    fn rejects_non_keys() {
+        let mut reader = Reader::new(ED25519_KEY);
+        let mut packets = packet::PacketIter::new(reader);
+        assert!(PublicKey::parse(&packets.next().unwrap().unwrap()).is_ok());
+        assert_eq!(
+            PublicKey::parse(&packets.next().unwrap().unwrap()).unwrap_err(),
+            Error::IllFormedKey
+        );
+        let mut v3 = ED25519_KEY[..53].to_owned();
+        v3[2] = 3;
+        reader = Reader::new(&v3);
+        assert_eq!(
+            PublicKey::parse(&packet::next(&mut reader).unwrap().unwrap()).unwrap_err(),
+            Error::UnsupportedKeyVersion
+        );
+    }
+
+    #[test]
+    fn parses_key() {
+        let packet = packet::next(&mut Reader::new(ED25519_KEY))
+            .unwrap()
+            .unwrap();
+        let key = PublicKey::parse(&packet).unwrap();
+        assert_eq!(key.algorithm(), PublicKeyAlgorithm::EdDsa.to_u8());
+        assert_eq!(key.creation_time(), 1611619200);
+        // 1.3.6.1.4.1.11591.15.1
+        assert_eq!(
+            key.curve_oid(),
+            Some(&b"\x2B\x06\x01\x04\x01\xDA\x47\x0F\x01"[..])
+        );
+        assert_eq!(key.mpis(), &ED25519_KEY[8..53]);
+        assert_eq!(key.mpis()[10..13], [1, 7, 0x40]);
+        let mut s = ED25519_KEY[..53].to_owned();
+        // truncate the point
+        s[1] -= 1;
+        let packet = packet::next(&mut Reader::new(&s[..52])).unwrap().unwrap();
+        assert_eq!(
+            PublicKey::parse(&packet).unwrap_err(),
+            Error::Truncated {
+                needed: 33,
+                available: 32
+            }
+        );
+        // reserved OID length
+        s[1] += 1;
+        s[8] = 0;
+        let packet = packet::next(&mut Reader::new(&s)).unwrap().unwrap();
+        assert_eq!(PublicKey::parse(&packet).unwrap_err(), Error::IllFormedKey);
+        s[7] = 200;
+        let packet = packet::next(&mut Reader::new(&s)).unwrap().unwrap();
+        assert_eq!(
+            PublicKey::parse(&packet).unwrap_err(),
+            Error::UnsupportedPkeyAlgorithm(200)
+        );
+    }
+
+    #[test]
+    fn key_material_by_algorithm() {
+        fn key(alg: u8, material: &[u8]) -> Vec<u8> {
+            let mut body = vec![4, 0x60, 0x0F, 0x77, 0x1A, alg];
+            body.extend_from_slice(material);
+            body
+        }
+        fn parse<'a>(body: &'a [u8]) -> Result<PublicKey<'a>, Error> {
+            PublicKey::parse(&packet::Packet::new(14, body).unwrap())
+        }
+        let mpis = |n| [0, 1, 1].repeat(n);
+        for &(alg, count) in &[
+            (PublicKeyAlgorithm::Rsa.to_u8(), 2),
+            (PublicKeyAlgorithm::RsaSign.to_u8(), 2),
+            (PublicKeyAlgorithm::ElGamalEncrypt.to_u8(), 3),
+            (PublicKeyAlgorithm::Dsa.to_u8(), 4),
+        ] {
+            let body = key(alg, &mpis(count));
+            let parsed = parse(&body).unwrap();
+            assert_eq!(parsed.algorithm(), alg);
+            assert_eq!(parsed.curve_oid(), None);
+            assert_eq!(parsed.mpis(), &body[6..]);
+            // one MPI too few or too many
+            let body = key(alg, &mpis(count - 1));
+            assert_eq!(
+                parse(&body).unwrap_err(),
+                Error::Truncated {
+                    needed: 2,
+                    available: 0
+                }
+            );
+            let body = key(alg, &mpis(count + 1));
+            assert_eq!(
+                parse(&body).unwrap_err(),
+                Error::TrailingJunk {
+                    offset: body.len() - 3
+                }
+            );
+        }
+        // the Curve25519 ECDH subkey of the test key
+        let subkey = packet::parse_single(&mut Reader::new(&ED25519_KEY[228..286])).unwrap();
+        let subkey = PublicKey::parse(&subkey).unwrap();
+        assert_eq!(subkey.algorithm(), PublicKeyAlgorithm::Ecdh.to_u8());
+        assert_eq!(subkey.curve_oid().map(<[u8]>::len), Some(10));
+        assert_eq!(subkey.mpis(), &ED25519_KEY[236..286]);
+        let mut ecdh = key(PublicKeyAlgorithm::Ecdh.to_u8(), &[1, 0x2B, 0, 1, 1]);
+        assert_eq!(
+            parse(&ecdh).unwrap_err(),
+            Error::Truncated {
+                needed: 2,
+                available: 0
+            }
+        );
+        ecdh.extend_from_slice(&[3, 1, 8, 9]);
+        assert!(parse(&ecdh).is_ok());
+        ecdh[12] = 2;
+        assert_eq!(parse(&ecdh).unwrap_err(), Error::IllFormedKey);
+        for &alg in &[0, 20, 21, 23, 100, 255] {
+            assert_eq!(
+                parse(&key(alg, &mpis(4))).unwrap_err(),
+                Error::UnsupportedPkeyAlgorithm(alg)
+            );
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn to_hashable() {
+        let mut keys = packet::PacketIter::new(Reader::new(ED25519_KEY))
+            .map(Result::unwrap)
+            .filter(|p| p.tag() == 6 || p.tag() == 14);
+        let packet = keys.next().unwrap();
+        let hashable = PublicKey::parse(&packet).unwrap().to_hashable();
+        assert_eq!(hashable[..3], [0x99, 0, 51]);
+        assert_eq!(hashable[3..], ED25519_KEY[2..53]);
+        #[cfg(feature = "sha1")]
+        for packet in keys {
+            let key = PublicKey::parse(&packet).unwrap();
+            let mut ctx = Sha1::new();
+            ctx.update(&key.to_hashable());
+            assert_eq!(ctx.finalize(), key.fingerprint());
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn user_id_binding_input() {
+        let mut packets = packet::PacketIter::new(Reader::new(SHA1_CERT_KEY)).map(Result::unwrap);
+        let key = PublicKey::parse(&packets.next().unwrap()).unwrap();
+        let uid = packets.next().unwrap();
+        let input = super::user_id_binding_input(&key, &uid);
+        assert_eq!(input[..54], key.to_hashable()[..]);
+        assert_eq!(input[54..59], [0xB4, 0, 0, 0, 29]);
+        assert_eq!(input[59..], SHA1_CERT_KEY[55..84]);
+        let mut expected = vec![0x99, 0, 51];
+        expected.extend_from_slice(&SHA1_CERT_KEY[2..53]);
+        expected.extend_from_slice(&[0xB4, 0, 0, 0, 29]);
+        expected.extend_from_slice(b"SHA-1 Test <sha1@example.com>");
+        assert_eq!(input, expected);
+        // the SHA-1 self-signature that follows covers exactly this
+        let sig = Signature::parse(&packets.next().unwrap()).unwrap();
+        assert_eq!(sig.hash_algorithm(), 2);
+        #[cfg(feature = "sha1")]
+        {
+            let mut ctx = Sha1::new();
+            ctx.update(&input);
+            ctx.update(sig.hashed_material());
+            ctx.update(&sig.trailer().unwrap());
+            let digest = ctx.finalize();
+            assert!(sig.quick_check(&digest));
+            ctx = Sha1::new();
+            ctx.update(&input[..input.len() - 1]);
+            ctx.update(sig.hashed_material());
+            ctx.update(&sig.trailer().unwrap());
+            assert!(!sig.quick_check(&ctx.finalize()));
+        }
+        assert!(packets.next().is_none());
+        let attribute = packet::Packet::new(17, &[3, 1, 0xAB, 0xCD]).unwrap();
+        let input = super::user_id_binding_input(&key, &attribute);
+        assert_eq!(input[54..], [0xD1, 0, 0, 0, 4, 3, 1, 0xAB, 0xCD]);
+    }
+
+    #[cfg(feature = "sha1")]
+    #[test]
+    fn fingerprint() {
+        let mut keys = packet::PacketIter::new(Reader::new(ED25519_KEY))
+            .map(Result::unwrap)
+            .filter(|p| p.tag() == 6 || p.tag() == 14)
+            .map(|p| PublicKey::parse(&p).unwrap());
+        let primary = keys.next().unwrap();
+        assert_eq!(
+            primary.fingerprint(),
+            *b"\x11\xEF\xDA\xDF\xAF\xBF\x21\x5A\xBE\x7B\x62\x4F\xE1\xB5\xE5\xB1\x30\xA4\xB4\x61"
+        );
+        assert_eq!(primary.key_id(), *b"\xE1\xB5\xE5\xB1\x30\xA4\xB4\x61");
+        let subkey = keys.next().unwrap();
+        assert_eq!(
+            subkey.fingerprint(),
+            *b"\x56\xF0\x9E\x8C\x77\x95\xC2\x3F\xBC\x22\x5C\x80\x1E\xD6\xF2\x39\xB9\x00\x4C\x2D"
+        );
+        assert_eq!(subkey.key_id(), *b"\x1E\xD6\xF2\x39\xB9\x00\x4C\x2D");
+        assert!(keys.next().is_none());
+    }
+
+    #[test]
+    fn is_expired() {
+        let mut reader = Reader::new(ED25519_KEY);
+        let key = PublicKey::parse(&packet::next(&mut reader).unwrap().unwrap()).unwrap();
+        let self_sig = packet::parse_single(&mut Reader::new(&ED25519_KEY[82..228])).unwrap();
+        let self_sig = Signature::parse(&self_sig).unwrap();
+        assert_eq!(self_sig.key_expiration(), None);
+        assert_eq!(key.is_expired(&self_sig, !0), None);
+        let with_expiration = |expiration: u32| {
+            let mut data = vec![0xC2, 19, 4, 0x13, 22, 8, 0, 6, 5, 9];
+            data.extend_from_slice(&expiration.to_be_bytes());
+            data.extend_from_slice(&[0, 0, 0x61, 0x58, 0, 1, 1]);
+            data
+        };
+        let created = key.creation_time();
+        for &(expiration, now, expected) in &[
+            (0, !0, None),
+            (86400, created, Some(false)),
+            (86400, created + 86399, Some(false)),
+            (86400, created + 86400, Some(true)),
+            (86400, !0, Some(true)),
+            (!0, !0, Some(false)),
+        ] {
+            let data = with_expiration(expiration);
+            let sig = packet::parse_single(&mut Reader::new(&data)).unwrap();
+            let sig = Signature::parse(&sig).unwrap();
+            assert_eq!(sig.key_expiration(), Some(expiration));
+            assert_eq!(key.is_expired(&sig, now), expected);
+        }
+    }
+}
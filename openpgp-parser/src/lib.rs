@@ -18,13 +18,40 @@
     not(any(bare_trait_obj_deprecated, bare_trait_obj_allowed)),
     not(any(ellipsis_inclusive_range_deprecated, ellipsis_inclusive_range_allowed)),
     not(any(alloc_crate_stable, alloc_crate_unstable)),
+    not(any(const_generics_stable, const_generics_unstable)),
 ))]
 compile_error!("build script bug");
 
-pub use buffer::{EOFError, Reader};
+#[cfg(any(feature = "std", test))]
+extern crate core;
+
+use core::fmt;
+
+pub use buffer::{BitReader, EOFError, Reader};
+pub mod algorithm;
+pub mod armor;
+// In a separate file so that older compilers never have to parse it
+#[cfg(const_generics_stable)]
+mod array;
 mod buffer;
+#[cfg(feature = "alloc")]
+pub mod cert;
+pub mod checksum;
+#[cfg(feature = "digest")]
+pub mod hashing;
+#[cfg(feature = "std")]
+pub mod io;
+pub mod key;
+pub mod message;
 pub mod packet;
+pub mod rpm;
+#[cfg(feature = "sha1")]
+mod sha1;
 pub mod signature;
+pub mod user_id;
+pub mod util;
+#[cfg(feature = "verify")]
+pub mod verify;
 
 #[cfg(target_pointer_width = "16")]
 compile_error!("Sorry, 16-bit targets not supported");
@@ -38,18 +65,39 @@ pub enum Error {
     PacketFirstBitZero,
     /// Length field is too long
     TooLong,
-    /// Packet is truncated
+    /// Packet is truncated.  This is used where the number of missing bytes
+    /// is not known; running out of data in a [`Reader`] results in
+    /// [`Error::Truncated`] instead.
     PrematureEOF,
-    /// Unsupported signature version
-    UnsupportedSignatureVersion,
+    /// Input is truncated: a [`Reader`] needed more bytes than it had left,
+    /// as recorded by an [`EOFError`]
+    Truncated {
+        /// The number of bytes that were needed
+        needed: usize,
+        /// The number of bytes that were available
+        available: usize,
+    },
+    /// Unsupported signature version.  This includes versions 5 and 6, which
+    /// are not supported yet.
+    UnsupportedSignatureVersion(u8),
     /// Indefinite-length old format packet detected.  These are not supported.
     IndefiniteLength,
-    /// Partial-length new format packet detected.  These are not supported.
+    /// Partial-length packet that `packet::next_partial` cannot reassemble:
+    /// its tag does not allow partial lengths, or its first chunk is shorter
+    /// than 512 bytes.  [`packet::next`] reports partial lengths as
+    /// [`Error::PartialLengthFirstByte`] instead.
     PartialLength,
-    /// Bad tag
-    BadTag,
+    /// Bad tag.  The packet tag 0 is reserved, and must not be used.  The
+    /// first byte of the offending packet header is included.
+    BadTag(u8),
     /// Trailing junk
-    TrailingJunk,
+    TrailingJunk {
+        /// The offset of the first unexpected byte.  For
+        /// [`packet::parse_single`], this is relative to the start of the
+        /// [`Reader`]; for junk inside a packet, it is relative to the start of
+        /// the packet body or subpacket it was found in.
+        offset: usize,
+    },
     /// Bogus MPI
     BadMPI,
     /// Ill-formed signature
@@ -74,6 +122,40 @@ pub enum Error {
     NoCreationTime,
     /// Unsupported critical subpacket
     UnsupportedCriticalSubpacket(u8),
+    /// Ill-formed public key
+    IllFormedKey,
+    /// Unsupported public key version
+    UnsupportedKeyVersion,
+    /// Bad ASCII armor
+    BadArmor,
+    /// Packet body is larger than the caller-imposed limit, or too large to
+    /// be addressed on this platform
+    PacketTooLarge,
+    /// New format packet or subpacket with a partial length (first length byte
+    /// in the range 224 to 254).  These are not supported.
+    PartialLengthFirstByte,
+    /// Bad RPM header magic
+    BadHeaderMagic,
+    /// Corrupt RPM header, such as an index entry that points outside of the
+    /// data section
+    CorruptHeader,
+    /// Packet length not encoded in the shortest possible form
+    NonCanonicalLength,
+    /// Too many levels of nesting
+    RecursionLimit,
+    /// Ill-formed message packet, or a packet of the wrong type
+    IllFormedMessage,
+    /// Text that must be UTF-8 is not valid UTF-8
+    InvalidUtf8,
+    /// Ill-formed user attribute packet
+    IllFormedUserAttribute,
+    /// More packets than the caller-imposed limit
+    TooManyPackets,
+    /// A packet tag that cannot be used, as passed to [`packet::Packet::new`]:
+    /// 0, which is reserved, or a tag above 63, which cannot be encoded
+    InvalidTag(u8),
+    /// Signature does not match the key it was checked against
+    BadSignature,
     /// Wrong signature type
     WrongSignatureType {
         /// The expected signature type
@@ -82,3 +164,202 @@ pub enum Error {
         actual_type: u8,
     },
 }
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::PacketFirstBitZero => f.write_str("first bit of packet is zero"),
+            Error::TooLong => f.write_str("length field is too long"),
+            Error::PrematureEOF => f.write_str("unexpected end of input"),
+            Error::Truncated { needed, available } => write!(
+                f,
+                "unexpected end of input: needed {} bytes, but only {} available",
+                needed, available
+            ),
+            Error::UnsupportedSignatureVersion(version) => {
+                write!(f, "unsupported signature version {}", version)
+            }
+            Error::IndefiniteLength => f.write_str("indefinite-length packets are not supported"),
+            Error::PartialLength => {
+                f.write_str("partial length not allowed for this packet or too short")
+            }
+            Error::BadTag(tagbyte) => write!(f, "bad packet tag (header byte {:#04X})", tagbyte),
+            Error::TrailingJunk { offset } => write!(f, "trailing junk at offset {}", offset),
+            Error::BadMPI => f.write_str("bogus multiprecision integer"),
+            Error::IllFormedSignature => f.write_str("ill-formed signature"),
+            Error::UnsupportedHashAlgorithm(alg) => {
+                write!(f, "unsupported hash algorithm {}", alg)
+            }
+            Error::UnknownPkeyAlgorithm(alg) => write!(f, "unknown public-key algorithm {}", alg),
+            Error::UnsupportedPkeyAlgorithm(alg) => {
+                write!(f, "unsupported public-key algorithm {}", alg)
+            }
+            Error::InsecureAlgorithm(alg) => write!(f, "insecure algorithm {}", alg),
+            Error::InvalidPkeyAlgorithm(alg) => {
+                write!(f, "public-key algorithm {} is not valid here", alg)
+            }
+            Error::PkeyAlgorithmRequiresV4Sig(alg) => write!(
+                f,
+                "public-key algorithm {} requires a version 4 signature",
+                alg
+            ),
+            Error::SignatureNotValidYet => f.write_str("signature not valid yet"),
+            Error::SignatureExpired => f.write_str("signature expired"),
+            Error::NoCreationTime => f.write_str("signature has no creation time"),
+            Error::UnsupportedCriticalSubpacket(tag) => {
+                write!(f, "unsupported critical subpacket {}", tag)
+            }
+            Error::IllFormedKey => f.write_str("ill-formed public key"),
+            Error::UnsupportedKeyVersion => f.write_str("unsupported public key version"),
+            Error::BadArmor => f.write_str("bad ASCII armor"),
+            Error::PacketTooLarge => f.write_str("packet is too large"),
+            Error::PartialLengthFirstByte => f.write_str("partial first length byte"),
+            Error::BadHeaderMagic => f.write_str("bad RPM header magic"),
+            Error::CorruptHeader => f.write_str("corrupt RPM header"),
+            Error::NonCanonicalLength => f.write_str("non-canonical packet length"),
+            Error::RecursionLimit => f.write_str("too many levels of nesting"),
+            Error::IllFormedMessage => f.write_str("ill-formed message packet"),
+            Error::InvalidUtf8 => f.write_str("invalid UTF-8"),
+            Error::IllFormedUserAttribute => f.write_str("ill-formed user attribute"),
+            Error::TooManyPackets => f.write_str("too many packets"),
+            Error::InvalidTag(tag) => write!(f, "invalid packet tag {}", tag),
+            Error::BadSignature => f.write_str("bad signature"),
+            Error::WrongSignatureType {
+                expected_type,
+                actual_type,
+            } => write!(
+                f,
+                "wrong signature type {} (expected {})",
+                actual_type,
+                expected_type.to_u8()
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Limits on the work done parsing untrusted data, for the entry points that
+/// take them.  Functions that do not take a [`ParseOptions`], such as
+/// [`packet::next`], behave as if given [`ParseOptions::default`].
+///
+/// ```rust
+/// # use openpgp_parser::ParseOptions;
+/// assert_eq!(ParseOptions::default().max_depth, 4);
+/// assert_eq!(ParseOptions::default().max_packets, None);
+/// assert!(ParseOptions::default().lenient_utf8);
+/// let strict = ParseOptions { max_depth: 1, ..ParseOptions::default() };
+/// assert_ne!(strict, ParseOptions::default());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    /// The maximum nesting depth of structures that can contain themselves,
+    /// such as signatures embedded in signatures.  Deeper nesting results in
+    /// [`Error::RecursionLimit`].  The default is
+    /// [`signature::MAX_EMBEDDING_DEPTH`].
+    pub max_depth: usize,
+    /// The maximum number of packets in a stream, or [`None`] for no limit.
+    /// Reading a packet past the limit results in [`Error::TooManyPackets`].
+    /// The default is no limit.
+    pub max_packets: Option<usize>,
+    /// Whether text that should be UTF-8, such as user IDs in
+    /// [`cert::split_certs_with_options`], is accepted even if it is not
+    /// valid UTF-8.  If `false`, such text results in [`Error::InvalidUtf8`].
+    /// The default is `true`, since real keyrings contain user IDs in legacy
+    /// encodings such as Latin-1.
+    pub lenient_utf8: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_depth: signature::MAX_EMBEDDING_DEPTH,
+            max_packets: None,
+            lenient_utf8: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn error_display() {
+        for &(err, msg) in &[
+            (Error::PacketFirstBitZero, "first bit of packet is zero"),
+            (
+                Error::PartialLength,
+                "partial length not allowed for this packet or too short",
+            ),
+            (Error::PrematureEOF, "unexpected end of input"),
+            (
+                Error::Truncated {
+                    needed: 4,
+                    available: 3,
+                },
+                "unexpected end of input: needed 4 bytes, but only 3 available",
+            ),
+            (Error::BadTag(0x80), "bad packet tag (header byte 0x80)"),
+            (Error::InvalidTag(64), "invalid packet tag 64"),
+            (Error::BadSignature, "bad signature"),
+            (
+                Error::TrailingJunk { offset: 17 },
+                "trailing junk at offset 17",
+            ),
+            (
+                Error::UnsupportedSignatureVersion(6),
+                "unsupported signature version 6",
+            ),
+            (
+                Error::UnsupportedHashAlgorithm(255),
+                "unsupported hash algorithm 255",
+            ),
+            (
+                Error::PkeyAlgorithmRequiresV4Sig(22),
+                "public-key algorithm 22 requires a version 4 signature",
+            ),
+            (
+                Error::WrongSignatureType {
+                    expected_type: signature::SignatureType::Text,
+                    actual_type: 0,
+                },
+                "wrong signature type 0 (expected 1)",
+            ),
+        ] {
+            assert_eq!(format!("{}", err), msg);
+        }
+        for err in &[
+            Error::TooLong,
+            Error::UnsupportedSignatureVersion(5),
+            Error::IndefiniteLength,
+            Error::BadMPI,
+            Error::IllFormedSignature,
+            Error::UnknownPkeyAlgorithm(0),
+            Error::UnsupportedPkeyAlgorithm(0),
+            Error::InsecureAlgorithm(1),
+            Error::InvalidPkeyAlgorithm(2),
+            Error::SignatureNotValidYet,
+            Error::SignatureExpired,
+            Error::NoCreationTime,
+            Error::UnsupportedCriticalSubpacket(20),
+            Error::IllFormedKey,
+            Error::UnsupportedKeyVersion,
+            Error::BadArmor,
+            Error::PacketTooLarge,
+            Error::PartialLengthFirstByte,
+            Error::BadHeaderMagic,
+            Error::CorruptHeader,
+            Error::NonCanonicalLength,
+            Error::RecursionLimit,
+            Error::IllFormedMessage,
+            Error::InvalidUtf8,
+            Error::IllFormedUserAttribute,
+            Error::TooManyPackets,
+            Error::InvalidTag(64),
+            Error::BadSignature,
+        ] {
+            assert!(!format!("{}", err).is_empty());
+        }
+    }
+}
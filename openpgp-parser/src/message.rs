@@ -0,0 +1,418 @@
+//! OpenPGP message packets
+//!
+//! These packets make up OpenPGP messages, as opposed to keys.  Nothing here
+//! decompresses or decrypts anything; the functions only split the packets
+//! into their fields.
+
+use super::packet::Packet;
+#[cfg(feature = "alloc")]
+use super::packet::PacketIter;
+use super::{Error, Reader};
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+/// The tag of a signature packet
+#[cfg(feature = "alloc")]
+const TAG_SIGNATURE: u8 = 2;
+/// The tag of a one-pass signature packet
+const TAG_ONE_PASS_SIGNATURE: u8 = 4;
+/// The tag of a compressed data packet
+const TAG_COMPRESSED_DATA: u8 = 8;
+/// The tag of a literal data packet
+const TAG_LITERAL_DATA: u8 = 11;
+
+/// A literal data packet (tag 11), which holds the data of a message
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Literal<'a> {
+    format: u8,
+    filename: &'a [u8],
+    timestamp: u32,
+    data: &'a [u8],
+}
+
+impl<'a> Literal<'a> {
+    /// The format of the data: `b'b'` for binary, `b't'` for text, or `b'u'`
+    /// for UTF-8 text
+    pub fn format(&self) -> u8 {
+        self.format
+    }
+
+    /// The file name.  This is not necessarily valid UTF-8, and may be empty.
+    pub fn filename(&self) -> &'a [u8] {
+        self.filename
+    }
+
+    /// The timestamp, usually the modification time of the file, or 0
+    pub fn timestamp(&self) -> u32 {
+        self.timestamp
+    }
+
+    /// The literal data itself.  For a signed message, this is what the
+    /// signature covers (after line ending normalization, for text signatures).
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+/// Parses a literal data packet.  Returns [`Error::IllFormedMessage`] if
+/// `packet` is not a literal data packet or has an unknown format, and
+/// [`Error::Truncated`] if it is truncated.
+///
+/// ```rust
+/// # use openpgp_parser::{packet::Packet, message::parse_literal};
+/// let packet = Packet::new(11, b"b\x05a.txt\0\0\0\x01hello").unwrap();
+/// let literal = parse_literal(&packet).unwrap();
+/// assert_eq!(literal.format(), b'b');
+/// assert_eq!(literal.filename(), b"a.txt");
+/// assert_eq!(literal.timestamp(), 1);
+/// assert_eq!(literal.data(), b"hello");
+/// ```
+pub fn parse_literal<'a>(packet: &Packet<'a>) -> Result<Literal<'a>, Error> {
+    if packet.tag() != TAG_LITERAL_DATA {
+        return Err(Error::IllFormedMessage);
+    }
+    let mut reader = Reader::new(packet.contents());
+    let format = match reader.byte()? {
+        format @ b'b' | format @ b't' | format @ b'u' => format,
+        _ => return Err(Error::IllFormedMessage),
+    };
+    let filename_len = reader.byte()?;
+    let filename = reader.get_bytes(filename_len.into())?;
+    let timestamp = reader.be_u32()?;
+    Ok(Literal {
+        format,
+        filename,
+        timestamp,
+        data: reader.as_untrusted_slice(),
+    })
+}
+
+/// A version 3 one-pass signature packet (tag 4), which comes before the
+/// signed data in a message and describes the signature that follows it
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OnePassSig {
+    sig_type: u8,
+    hash_alg: u8,
+    pkey_alg: u8,
+    key_id: [u8; 8],
+    nested: bool,
+}
+
+impl OnePassSig {
+    /// The raw signature type
+    pub fn signature_type(&self) -> u8 {
+        self.sig_type
+    }
+
+    /// The raw hash algorithm.  The signed data must be hashed with this
+    /// algorithm.
+    pub fn hash_algorithm(&self) -> u8 {
+        self.hash_alg
+    }
+
+    /// The raw public-key algorithm
+    pub fn pk_algorithm(&self) -> u8 {
+        self.pkey_alg
+    }
+
+    /// The key ID of the signing key
+    pub fn key_id(&self) -> [u8; 8] {
+        self.key_id
+    }
+
+    /// Checks if the next packet is another one-pass signature over the same
+    /// data.  On the wire, this is a flag byte of 0; the last one-pass
+    /// signature before the data has a flag byte of 1.
+    pub fn is_nested(&self) -> bool {
+        self.nested
+    }
+}
+
+/// Parses a one-pass signature packet.  Returns
+/// [`Error::UnsupportedSignatureVersion`] if the version is not 3, and
+/// [`Error::IllFormedMessage`] if `packet` is not a one-pass signature packet
+/// or its nested flag is not 0 or 1.
+///
+/// ```rust
+/// # use openpgp_parser::{packet::Packet, message::parse_one_pass_signature};
+/// let body = [3, 0, 8, 22, 1, 2, 3, 4, 5, 6, 7, 8, 1];
+/// let ops = parse_one_pass_signature(&Packet::new(4, &body).unwrap()).unwrap();
+/// assert_eq!(ops.signature_type(), 0);
+/// assert_eq!(ops.hash_algorithm(), 8);
+/// assert_eq!(ops.pk_algorithm(), 22);
+/// assert_eq!(ops.key_id(), [1, 2, 3, 4, 5, 6, 7, 8]);
+/// assert!(!ops.is_nested());
+/// ```
+pub fn parse_one_pass_signature(packet: &Packet) -> Result<OnePassSig, Error> {
+    if packet.tag() != TAG_ONE_PASS_SIGNATURE {
+        return Err(Error::IllFormedMessage);
+    }
+    Reader::read_all_strict(packet.contents(), |reader| {
+        match reader.byte()? {
+            3 => {}
+            version => return Err(Error::UnsupportedSignatureVersion(version)),
+        }
+        let sig_type = reader.byte()?;
+        let hash_alg = reader.byte()?;
+        let pkey_alg = reader.byte()?;
+        let mut key_id = [0u8; 8];
+        key_id.copy_from_slice(reader.get_bytes(8)?);
+        let nested = match reader.byte()? {
+            0 => true,
+            1 => false,
+            _ => return Err(Error::IllFormedMessage),
+        };
+        Ok(OnePassSig {
+            sig_type,
+            hash_alg,
+            pkey_alg,
+            key_id,
+            nested,
+        })
+    })
+}
+
+/// Splits a compressed data packet (tag 8) into its compression algorithm
+/// (0 for uncompressed, 1 for ZIP, 2 for ZLIB, or 3 for BZip2) and the
+/// compressed data, which can be passed to an external decompressor.  Returns
+/// [`Error::IllFormedMessage`] if `packet` is not a compressed data packet.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, packet::Packet, message::compression_algorithm};
+/// let packet = Packet::new(8, &[2, 0x78, 0x9C]).unwrap();
+/// assert_eq!(compression_algorithm(&packet).unwrap(), (2, &[0x78, 0x9C][..]));
+/// let packet = Packet::new(11, &[2, 0x78, 0x9C]).unwrap();
+/// assert_eq!(compression_algorithm(&packet).unwrap_err(), Error::IllFormedMessage);
+/// ```
+pub fn compression_algorithm<'a>(packet: &Packet<'a>) -> Result<(u8, &'a [u8]), Error> {
+    if packet.tag() != TAG_COMPRESSED_DATA {
+        return Err(Error::IllFormedMessage);
+    }
+    let mut reader = Reader::new(packet.contents());
+    let algorithm = reader.byte()?;
+    Ok((algorithm, reader.as_untrusted_slice()))
+}
+
+/// Reads the hash algorithm of a signature packet body, without parsing the
+/// rest of the signature
+#[cfg(feature = "alloc")]
+fn signature_hash_algorithm(body: &[u8]) -> Result<u8, Error> {
+    let mut reader = Reader::new(body);
+    // the hash algorithm follows the key ID of a version 3 signature, and the
+    // public-key algorithm of a version 4 signature
+    let skip = match reader.byte()? {
+        3 => 15,
+        4 => 2,
+        version => return Err(Error::UnsupportedSignatureVersion(version)),
+    };
+    reader.skip(skip)?;
+    Ok(reader.byte()?)
+}
+
+/// Collects the hash algorithms used by the signature and one-pass signature
+/// packets in `reader`, so that a verifier can set up only the hash contexts
+/// it needs before reading the signed data.  Each algorithm is only returned
+/// once, in the order they are first found.  The algorithms are not checked,
+/// and may include insecure or unknown ones.
+///
+/// Only as much of each signature is parsed as is needed to find its hash
+/// algorithm, so a signature can be malformed even if this succeeds.
+/// Signatures inside compressed data packets are not found.  Errors leave
+/// `reader` unchanged; otherwise, all of `reader` is consumed.
+///
+/// ```rust
+/// # use openpgp_parser::{Reader, message::required_hash_algorithms};
+/// let data = [
+///     0xC4, 13, 3, 0, 10, 22, 1, 2, 3, 4, 5, 6, 7, 8, 1,
+///     0xCB, 6, b'b', 0, 0, 0, 0, 0,
+///     0xC2, 4, 4, 0, 22, 10,
+/// ];
+/// assert_eq!(required_hash_algorithms(&mut Reader::new(&data)).unwrap(), [10]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn required_hash_algorithms(reader: &mut Reader) -> Result<alloc::vec::Vec<u8>, Error> {
+    let mut algorithms = alloc::vec::Vec::new();
+    for packet in PacketIter::new(*reader) {
+        let packet = packet?;
+        let algorithm = match packet.tag() {
+            TAG_SIGNATURE => signature_hash_algorithm(packet.contents())?,
+            TAG_ONE_PASS_SIGNATURE => parse_one_pass_signature(&packet)?.hash_algorithm(),
+            _ => continue,
+        };
+        if !algorithms.contains(&algorithm) {
+            algorithms.push(algorithm)
+        }
+    }
+    let len = reader.len();
+    reader.skip(len)?;
+    Ok(algorithms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn literal_data() {
+        let body = b"t\x00\x60\x0F\x77\x1Aline\r\n";
+        let literal = parse_literal(&Packet::new(TAG_LITERAL_DATA, body).unwrap()).unwrap();
+        assert_eq!(
+            (literal.format(), literal.filename(), literal.timestamp()),
+            (b't', &b""[..], 0x600F771A)
+        );
+        assert_eq!(literal.data(), b"line\r\n");
+        // no data at all is fine
+        let literal = parse_literal(&Packet::new(TAG_LITERAL_DATA, &body[..6]).unwrap());
+        assert_eq!(literal.unwrap().data(), b"");
+        for len in 0..6 {
+            let packet = Packet::new(TAG_LITERAL_DATA, &body[..len]).unwrap();
+            match parse_literal(&packet) {
+                Err(Error::Truncated { .. }) => {}
+                other => panic!("{:?}", other),
+            }
+        }
+        // filename longer than the packet
+        let packet = Packet::new(TAG_LITERAL_DATA, b"u\x10abc").unwrap();
+        assert_eq!(
+            parse_literal(&packet),
+            Err(Error::Truncated {
+                needed: 16,
+                available: 3
+            })
+        );
+        for &format in &[b'l', b'1', b'm', 0] {
+            let body = [format, 0, 0, 0, 0, 0];
+            let packet = Packet::new(TAG_LITERAL_DATA, &body).unwrap();
+            assert_eq!(parse_literal(&packet), Err(Error::IllFormedMessage));
+        }
+        let packet = Packet::new(TAG_COMPRESSED_DATA, body).unwrap();
+        assert_eq!(parse_literal(&packet), Err(Error::IllFormedMessage));
+    }
+
+    #[test]
+    fn one_pass_signature() {
+        let body = [
+            3, 0, 8, 22, 0xE1, 0xB5, 0xE5, 0xB1, 0x30, 0xA4, 0xB4, 0x61, 0,
+        ];
+        let packet = Packet::new(TAG_ONE_PASS_SIGNATURE, &body).unwrap();
+        let ops = parse_one_pass_signature(&packet).unwrap();
+        assert!(ops.is_nested());
+        assert_eq!(ops.key_id(), *b"\xE1\xB5\xE5\xB1\x30\xA4\xB4\x61");
+        for len in 0..body.len() {
+            let packet = Packet::new(TAG_ONE_PASS_SIGNATURE, &body[..len]).unwrap();
+            match parse_one_pass_signature(&packet) {
+                Err(Error::Truncated { .. }) => {}
+                other => panic!("{:?}", other),
+            }
+        }
+        let mut long = body.to_vec();
+        long.push(0);
+        let packet = Packet::new(TAG_ONE_PASS_SIGNATURE, &long).unwrap();
+        assert_eq!(
+            parse_one_pass_signature(&packet),
+            Err(Error::TrailingJunk { offset: 13 })
+        );
+        for &(index, value, err) in &[
+            (0, 4, Error::UnsupportedSignatureVersion(4)),
+            (0, 2, Error::UnsupportedSignatureVersion(2)),
+            (12, 2, Error::IllFormedMessage),
+        ] {
+            let mut bad = body;
+            bad[index] = value;
+            let packet = Packet::new(TAG_ONE_PASS_SIGNATURE, &bad).unwrap();
+            assert_eq!(parse_one_pass_signature(&packet), Err(err));
+        }
+        let packet = Packet::new(2, &body).unwrap();
+        assert_eq!(
+            parse_one_pass_signature(&packet),
+            Err(Error::IllFormedMessage)
+        );
+    }
+
+    #[test]
+    fn compressed_data() {
+        let packet = Packet::new(TAG_COMPRESSED_DATA, &[0]).unwrap();
+        assert_eq!(compression_algorithm(&packet), Ok((0, &[][..])));
+        let packet = Packet::new(TAG_COMPRESSED_DATA, &[]).unwrap();
+        assert_eq!(
+            compression_algorithm(&packet),
+            Err(Error::Truncated {
+                needed: 1,
+                available: 0
+            })
+        );
+        // the format of the packet does not matter
+        let data = [0xA0, 2, 1, 5];
+        let packet = super::super::packet::next(&mut Reader::new(&data))
+            .unwrap()
+            .unwrap();
+        assert_eq!(compression_algorithm(&packet), Ok((1, &[5][..])));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn required_hash_algorithms() {
+        let eddsa = include_bytes!("../../data/eddsa.asc");
+        let v3 = include_bytes!("../../data/v3-rsa.sig");
+        let ops = [0xC4, 13, 3, 0, 10, 22, 1, 2, 3, 4, 5, 6, 7, 8, 1];
+        let mut message = ops.to_vec();
+        message.extend_from_slice(&[0xCB, 6, b'b', 0, 0, 0, 0, 0]);
+        message.extend_from_slice(eddsa);
+        message.extend_from_slice(v3);
+        message.extend_from_slice(&[0xC2, 4, 4, 0, 22, 10]);
+        let mut reader = Reader::new(&message);
+        assert_eq!(
+            super::required_hash_algorithms(&mut reader).unwrap(),
+            [10, 8]
+        );
+        assert!(reader.is_empty());
+        assert_eq!(reader.offset(), message.len());
+        for data in &[&eddsa[..], &v3[..]] {
+            let mut reader = Reader::new(data);
+            assert_eq!(super::required_hash_algorithms(&mut reader).unwrap(), [8]);
+        }
+        let mut reader = Reader::new(&message[15..23]);
+        assert!(super::required_hash_algorithms(&mut reader)
+            .unwrap()
+            .is_empty());
+        // errors leave the reader alone
+        for &(data, err) in &[
+            (
+                &[0xC2, 3, 4, 0, 22][..],
+                Error::Truncated {
+                    needed: 1,
+                    available: 0,
+                },
+            ),
+            (
+                &[0xC2, 16, 3, 5, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8, 1],
+                Error::Truncated {
+                    needed: 1,
+                    available: 0,
+                },
+            ),
+            (
+                &[0xC2, 4, 5, 0, 22, 8],
+                Error::UnsupportedSignatureVersion(5),
+            ),
+            (
+                &[0xC4, 13, 3, 0, 10, 22, 1, 2, 3, 4, 5, 6, 7, 8, 2],
+                Error::IllFormedMessage,
+            ),
+            (
+                &message[..message.len() - 1],
+                Error::Truncated {
+                    needed: 4,
+                    available: 3,
+                },
+            ),
+        ] {
+            let mut reader = Reader::new(data);
+            assert_eq!(
+                super::required_hash_algorithms(&mut reader).unwrap_err(),
+                err
+            );
+            assert_eq!(reader.len(), data.len());
+        }
+    }
+}
@@ -1,8 +1,9 @@
 //! Utility functions for parsing OpenPGP packets
 
-use super::{Error, Reader};
+use super::{Error, ParseOptions, Reader};
 #[cfg(feature = "alloc")]
 extern crate alloc;
+extern crate core;
 
 /// The format of a packet
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq, Hash)]
@@ -14,24 +15,183 @@ pub enum Format {
     New = 0x40,
 }
 
-/// An OpenPGP packet
+/// The type of a packet, as indicated by its tag
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum PacketType {
+    /// Public-key encrypted session key (tag 1)
+    PublicKeyEncryptedSessionKey,
+    /// Signature (tag 2)
+    Signature,
+    /// Symmetric-key encrypted session key (tag 3)
+    SymmetricKeyEncryptedSessionKey,
+    /// One-pass signature (tag 4)
+    OnePassSignature,
+    /// Secret key (tag 5)
+    SecretKey,
+    /// Public key (tag 6)
+    PublicKey,
+    /// Secret subkey (tag 7)
+    SecretSubkey,
+    /// Compressed data (tag 8)
+    CompressedData,
+    /// Symmetrically encrypted data (tag 9)
+    SymmetricallyEncryptedData,
+    /// Marker (tag 10)
+    Marker,
+    /// Literal data (tag 11)
+    LiteralData,
+    /// Trust (tag 12)
+    Trust,
+    /// User ID (tag 13)
+    UserId,
+    /// Public subkey (tag 14)
+    PublicSubkey,
+    /// User attribute (tag 17)
+    UserAttribute,
+    /// Symmetrically encrypted and integrity protected data (tag 18)
+    SymmetricallyEncryptedIntegrityProtectedData,
+    /// Modification detection code (tag 19)
+    ModificationDetectionCode,
+    /// Any other tag
+    Unknown(u8),
+}
+
+impl PacketType {
+    /// Converts a raw packet tag to a [`PacketType`]
+    ///
+    /// ```rust
+    /// # use openpgp_parser::packet::PacketType;
+    /// assert_eq!(PacketType::from_u8(2), PacketType::Signature);
+    /// assert_eq!(PacketType::from_u8(60), PacketType::Unknown(60));
+    /// ```
+    pub fn from_u8(tag: u8) -> Self {
+        match tag {
+            1 => PacketType::PublicKeyEncryptedSessionKey,
+            2 => PacketType::Signature,
+            3 => PacketType::SymmetricKeyEncryptedSessionKey,
+            4 => PacketType::OnePassSignature,
+            5 => PacketType::SecretKey,
+            6 => PacketType::PublicKey,
+            7 => PacketType::SecretSubkey,
+            8 => PacketType::CompressedData,
+            9 => PacketType::SymmetricallyEncryptedData,
+            10 => PacketType::Marker,
+            11 => PacketType::LiteralData,
+            12 => PacketType::Trust,
+            13 => PacketType::UserId,
+            14 => PacketType::PublicSubkey,
+            17 => PacketType::UserAttribute,
+            18 => PacketType::SymmetricallyEncryptedIntegrityProtectedData,
+            19 => PacketType::ModificationDetectionCode,
+            tag => PacketType::Unknown(tag),
+        }
+    }
+
+    /// Converts a [`PacketType`] to a raw packet tag
+    ///
+    /// ```rust
+    /// # use openpgp_parser::packet::PacketType;
+    /// assert_eq!(PacketType::UserId.to_u8(), 13);
+    /// assert_eq!(PacketType::Unknown(60).to_u8(), 60);
+    /// ```
+    pub fn to_u8(self) -> u8 {
+        match self {
+            PacketType::PublicKeyEncryptedSessionKey => 1,
+            PacketType::Signature => 2,
+            PacketType::SymmetricKeyEncryptedSessionKey => 3,
+            PacketType::OnePassSignature => 4,
+            PacketType::SecretKey => 5,
+            PacketType::PublicKey => 6,
+            PacketType::SecretSubkey => 7,
+            PacketType::CompressedData => 8,
+            PacketType::SymmetricallyEncryptedData => 9,
+            PacketType::Marker => 10,
+            PacketType::LiteralData => 11,
+            PacketType::Trust => 12,
+            PacketType::UserId => 13,
+            PacketType::PublicSubkey => 14,
+            PacketType::UserAttribute => 17,
+            PacketType::SymmetricallyEncryptedIntegrityProtectedData => 18,
+            PacketType::ModificationDetectionCode => 19,
+            PacketType::Unknown(tag) => tag,
+        }
+    }
+}
+
+/// An OpenPGP packet.  Two packets are equal if they have the same tag,
+/// format, and contents.
 #[derive(Clone, Debug)]
 pub struct Packet<'a> {
-    tag: u8,
-    buffer: &'a [u8],
+    pub(crate) tag: u8,
+    pub(crate) format: Format,
+    pub(crate) buffer: &'a [u8],
+    /// The header and contents, if they were read from a single buffer
+    pub(crate) raw: Option<&'a [u8]>,
+}
+
+impl<'a> PartialEq for Packet<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.tag == other.tag && self.format == other.format && self.buffer == other.buffer
+    }
+}
+
+impl<'a> Eq for Packet<'a> {}
+
+impl<'a> core::hash::Hash for Packet<'a> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.tag.hash(state);
+        self.format.hash(state);
+        self.buffer.hash(state);
+    }
 }
 
 pub(crate) fn get_varlen_bytes<'a>(reader: &mut Reader<'a>) -> Result<&'a [u8], Error> {
     let keybyte: u8 = reader.byte()?;
-    let len: usize = match keybyte {
+    get_varlen_body(keybyte, reader)
+}
+
+fn get_varlen_body<'a>(keybyte: u8, reader: &mut Reader<'a>) -> Result<&'a [u8], Error> {
+    let len = get_varlen_len(keybyte, reader)?;
+    Ok(reader.get_bytes(len)?)
+}
+
+/// Converts a 32-bit body length to a `usize`.  No slice can be longer than
+/// `isize::MAX` bytes, which is less than the largest 32-bit length on 32-bit
+/// platforms, so longer lengths result in [`Error::PacketTooLarge`].
+fn body_len(len: u32) -> Result<usize, Error> {
+    if u64::from(len) > (!0usize >> 1) as u64 {
+        Err(Error::PacketTooLarge)
+    } else {
+        Ok(len as usize)
+    }
+}
+
+/// Checks that a body of `len` bytes fits in a packet header, and so can be
+/// serialized
+fn check_serializable_len(len: usize) -> Result<(), Error> {
+    if len as u64 > u64::from(!0u32) {
+        Err(Error::PacketTooLarge)
+    } else {
+        Ok(())
+    }
+}
+
+fn get_varlen_len(keybyte: u8, reader: &mut Reader) -> Result<usize, Error> {
+    Ok(match keybyte {
         0...191 => keybyte.into(),
-        192...223 => ((usize::from(keybyte) - 192) << 8) + usize::from(reader.byte()?) + 192,
-        255 => reader.be_u32()? as _,
+        192...223 => {
+            // The match arm guarantees that this cannot underflow, and the
+            // result is at most (31 << 8) + 255 + 192 = 8383
+            let high = usize::from(keybyte - 192);
+            debug_assert!(high < 32);
+            (high << 8) + usize::from(reader.byte()?) + 192
+        }
+        255 => body_len(reader.be_u32()?)?,
         // Partial lengths are deliberately unsupported, as we don’t handle PGP signed and/or
         // encrypted data ourselves.
-        _ => return Err(Error::PartialLength),
-    };
-    Ok(reader.get_bytes(len)?)
+        _ => return Err(Error::PartialLengthFirstByte),
+    })
 }
 
 /// Read a packet from `reader`.  Returns:
@@ -40,98 +200,1370 @@ pub(crate) fn get_varlen_bytes<'a>(reader: &mut Reader<'a>) -> Result<&'a [u8],
 /// - `Ok(None)` if the reader is empty.
 /// - `Err` if an error occurred, such as trailing junk.
 pub fn next<'a>(reader: &mut Reader<'a>) -> Result<Option<Packet<'a>>, Error> {
+    next_with_limit(reader, !0)
+}
+
+/// Same as [`next`], except that if the declared length of the packet body
+/// exceeds `max_body`, [`Error::PacketTooLarge`] is returned without
+/// attempting to read the body.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, Reader, packet::next_with_limit};
+/// let mut reader = Reader::new(&[0xC2, 2, 0, 0]);
+/// assert_eq!(next_with_limit(&mut reader.clone(), 1).unwrap_err(), Error::PacketTooLarge);
+/// assert_eq!(next_with_limit(&mut reader, 2).unwrap().unwrap().contents(), &[0, 0]);
+/// // The check happens before the body is read
+/// let mut reader = Reader::new(&[0xC2, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+/// assert_eq!(next_with_limit(&mut reader, 1 << 20).unwrap_err(), Error::PacketTooLarge);
+/// ```
+pub fn next_with_limit<'a>(
+    reader: &mut Reader<'a>,
+    max_body: usize,
+) -> Result<Option<Packet<'a>>, Error> {
+    let start = reader.as_untrusted_slice();
+    let packet = match read_header(reader)? {
+        None => return Ok(None),
+        Some((_, _, _, len)) if len > max_body => return Err(Error::PacketTooLarge),
+        Some((tagbyte, tag, format, len)) => {
+            let buffer = reader.get_bytes(len)?;
+            if tag == 0 {
+                return Err(Error::BadTag(tagbyte));
+            }
+            Packet {
+                tag,
+                format,
+                buffer,
+                raw: Some(&start[..start.len() - reader.len()]),
+            }
+        }
+    };
+    Ok(Some(packet))
+}
+
+/// Same as [`next`], except that new-format packets must use the shortest
+/// possible length encoding, or [`Error::NonCanonicalLength`] is returned.
+/// Old-format packets are accepted as in [`next`].
+///
+/// ```rust
+/// # use openpgp_parser::{Error, Reader, packet::next_strict};
+/// assert!(next_strict(&mut Reader::new(&[0xC2, 1, 5])).is_ok());
+/// assert_eq!(
+///     next_strict(&mut Reader::new(&[0xC2, 0xFF, 0, 0, 0, 1, 5])).unwrap_err(),
+///     Error::NonCanonicalLength,
+/// );
+/// ```
+pub fn next_strict<'a>(reader: &mut Reader<'a>) -> Result<Option<Packet<'a>>, Error> {
+    match next(reader)? {
+        Some(ref packet) if packet.format == Format::New && !packet.has_minimal_length() => {
+            Err(Error::NonCanonicalLength)
+        }
+        packet => Ok(packet),
+    }
+}
+
+/// Reads a packet header, returning the first byte of the header, the
+/// (possibly zero) tag, the format, and the length of the body.
+pub(crate) fn read_header(reader: &mut Reader) -> Result<Option<(u8, u8, Format, usize)>, Error> {
     let tagbyte: u8 = match reader.maybe_byte() {
         Some(e) if e & 0x80 == 0 => return Err(Error::PacketFirstBitZero),
         Some(e) => e,
         None => return Ok(None),
     };
-    let packet = if tagbyte & 0x40 == 0 {
+    let header = if tagbyte & 0x40 == 0 {
         let lenlen = 1u8 << (tagbyte & 0b11);
         // We deliberately do not support indefinite-length packets.
         if lenlen > 4 {
-            return Err(Error::PartialLength);
+            return Err(Error::IndefiniteLength);
         }
-        let mut len = 0usize;
-        for &i in reader.get_bytes(usize::from(lenlen))? {
-            len = len << 8 | usize::from(i)
+        // At most 4 bytes, so this cannot overflow
+        let len = reader
+            .get_bytes(usize::from(lenlen))?
+            .iter()
+            .fold(0u32, |len, &i| len << 8 | u32::from(i));
+        (tagbyte, 0xF & (tagbyte >> 2), Format::Old, body_len(len)?)
+    } else {
+        let keybyte = reader.byte()?;
+        let len = get_varlen_len(keybyte, reader)?;
+        (tagbyte, tagbyte & 0x3F, Format::New, len)
+    };
+    Ok(Some(header))
+}
+
+/// Reads exactly one packet from `reader`.  Returns [`Error::PrematureEOF`] if
+/// `reader` is empty, and [`Error::TrailingJunk`] with the offset of the
+/// first byte after the packet if anything follows it.  A caller that instead
+/// calls [`next`] until it returns `Ok(None)` cannot tell a clean end of input
+/// from junk that happens to parse as a packet.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, Reader, packet::parse_single};
+/// assert_eq!(parse_single(&mut Reader::new(&[0xC2, 1, 5])).unwrap().contents(), &[5]);
+/// assert_eq!(
+///     parse_single(&mut Reader::new(&[0xC2, 1, 5, 0xC2, 0])).unwrap_err(),
+///     Error::TrailingJunk { offset: 3 },
+/// );
+/// assert_eq!(parse_single(&mut Reader::empty()).unwrap_err(), Error::PrematureEOF);
+/// ```
+pub fn parse_single<'a>(reader: &mut Reader<'a>) -> Result<Packet<'a>, Error> {
+    let packet = next(reader)?.ok_or(Error::PrematureEOF)?;
+    if reader.is_empty() {
+        Ok(packet)
+    } else {
+        Err(Error::TrailingJunk {
+            offset: reader.offset(),
+        })
+    }
+}
+
+/// Encodes the header of a new-format packet with tag `tag` and a body of
+/// `len` bytes, using the shortest length encoding.  Returns the header and
+/// the number of bytes of it that are used.  Partial lengths are never used,
+/// so the first length byte is never in the range 224 to 254.  Only the low
+/// 32 bits of `len` are encoded; callers must check that `len` fits.
+fn new_format_header(tag: u8, len: usize) -> ([u8; 6], usize) {
+    let tag_byte = tag | 0b1100_0000u8;
+    match len {
+        // 1-byte
+        0...191 => ([tag_byte, len as u8, 0, 0, 0, 0], 2),
+        // 2-byte: the first byte is 192 to 223
+        192...8383 => {
+            let len = len - 192;
+            ([tag_byte, (len >> 8) as u8 + 192, len as u8, 0, 0, 0], 3)
         }
-        Packet {
-            tag: 0xF & (tagbyte >> 2),
-            buffer: reader.get_bytes(len)?,
+        // 5-byte
+        _ => (
+            [
+                tag_byte,
+                0xFF,
+                (len >> 24) as u8,
+                (len >> 16) as u8,
+                (len >> 8) as u8,
+                len as u8,
+            ],
+            6,
+        ),
+    }
+}
+
+/// Counts the packets in `reader`, consuming it.  This does not allocate, and
+/// returns the same errors as [`next`], so a count is only returned if the
+/// whole stream is well-formed.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, Reader, packet::count_packets};
+/// let mut reader = Reader::new(&[0xC2, 1, 5, 0x88, 0, 0xC2, 0]);
+/// assert_eq!(count_packets(&mut reader).unwrap(), 3);
+/// assert!(reader.is_empty());
+/// let mut reader = Reader::new(&[0xC2, 1, 5, 0xC0, 0]);
+/// assert_eq!(count_packets(&mut reader).unwrap_err(), Error::BadTag(0xC0));
+/// ```
+pub fn count_packets(reader: &mut Reader) -> Result<usize, Error> {
+    let mut count = 0;
+    while skip_packet(reader)?.is_some() {
+        count += 1
+    }
+    Ok(count)
+}
+
+/// Counts the packets in `reader` by tag, consuming it.  Element `i` of the
+/// result is the number of packets with tag `i`; element 0 is always zero, as
+/// tag 0 is rejected.  Like [`count_packets`], this does not allocate, and
+/// returns the same errors as [`next`].  Counts saturate at `u32::MAX`.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, Reader, packet::tag_histogram};
+/// let mut reader = Reader::new(&[0xC2, 1, 5, 0xA0, 0, 0xC2, 0]);
+/// let histogram = tag_histogram(&mut reader).unwrap();
+/// assert_eq!((histogram[2], histogram[8]), (2, 1));
+/// assert_eq!(histogram.iter().sum::<u32>(), 3);
+/// ```
+pub fn tag_histogram(reader: &mut Reader) -> Result<[u32; 64], Error> {
+    let mut histogram = [0u32; 64];
+    while let Some((tag, _)) = skip_packet(reader)? {
+        let count = &mut histogram[usize::from(tag)];
+        *count = count.saturating_add(1)
+    }
+    Ok(histogram)
+}
+
+/// Checks whether `input` is a sequence of packets that is already in
+/// canonical form: that is, whether serializing every packet in it with
+/// [`Packet::serialize`] would reproduce `input` exactly.  This requires every
+/// packet to be a new-format packet with the shortest possible length.  Empty
+/// input is canonical.  Errors are the same as those of [`next`].
+///
+/// ```rust
+/// # use openpgp_parser::packet::is_canonical;
+/// assert_eq!(is_canonical(&[]), Ok(true));
+/// assert_eq!(is_canonical(&[0xC2, 1, 5, 0xCD, 0]), Ok(true));
+/// assert_eq!(is_canonical(&[0xC2, 1, 5, 0x88, 1, 5]), Ok(false));
+/// assert!(is_canonical(&[0xC2, 1]).is_err());
+/// ```
+pub fn is_canonical(input: &[u8]) -> Result<bool, Error> {
+    let mut reader = Reader::new(input);
+    let mut canonical = true;
+    while let Some(packet) = next(&mut reader)? {
+        canonical &= packet.format == Format::New && packet.has_minimal_length();
+    }
+    Ok(canonical)
+}
+
+/// Skips over a packet, returning its tag and the length of its body.  The
+/// errors are the same as those of [`next`].
+fn skip_packet(reader: &mut Reader) -> Result<Option<(u8, usize)>, Error> {
+    match read_header(reader)? {
+        None => Ok(None),
+        Some((tagbyte, tag, _, len)) => {
+            reader.skip(len)?;
+            if tag != 0 {
+                Ok(Some((tag, len)))
+            } else {
+                Err(Error::BadTag(tagbyte))
+            }
         }
-    } else {
-        let buffer = get_varlen_bytes(reader)?;
-        Packet {
-            tag: tagbyte & 0x3F,
-            buffer,
+    }
+}
+
+/// The result of [`scan_all`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScanResult {
+    /// Number of packets successfully parsed
+    pub packets: usize,
+    /// `Ok(())` if the whole input was parsed, otherwise the first error
+    pub result: Result<(), Error>,
+    /// Offset of the end of the last packet successfully parsed.  If
+    /// [`Self::result`] is `Ok`, this is the length of the input; otherwise,
+    /// it is the offset of the packet that could not be parsed.
+    pub offset: usize,
+}
+
+/// Parses all of the packets in `input`, stopping at the first error.  This
+/// only checks the packet framing, not the packet contents.
+///
+/// This function never panics, whatever the input, so it can be used as a
+/// fuzz target.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, packet::{scan_all, ScanResult}};
+/// assert_eq!(
+///     scan_all(&[0xC2, 1, 5, 0xC2, 0, 0xC2, 1]),
+///     ScanResult {
+///         packets: 2,
+///         result: Err(Error::Truncated { needed: 1, available: 0 }),
+///         offset: 5,
+///     },
+/// );
+/// assert_eq!(scan_all(&[]), ScanResult { packets: 0, result: Ok(()), offset: 0 });
+/// ```
+pub fn scan_all(input: &[u8]) -> ScanResult {
+    scan_all_with_options(input, &ParseOptions::default())
+}
+
+/// Same as [`scan_all`], but stops with [`Error::TooManyPackets`] if `input`
+/// has more than [`ParseOptions::max_packets`] packets.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, ParseOptions, packet::{scan_all_with_options, ScanResult}};
+/// let options = ParseOptions { max_packets: Some(2), ..ParseOptions::default() };
+/// assert_eq!(
+///     scan_all_with_options(&[0xC2, 1, 5, 0xC2, 0, 0xC2, 0], &options),
+///     ScanResult { packets: 2, result: Err(Error::TooManyPackets), offset: 5 },
+/// );
+/// assert_eq!(scan_all_with_options(&[0xC2, 0, 0xC2, 0], &options).result, Ok(()));
+/// ```
+pub fn scan_all_with_options(input: &[u8], options: &ParseOptions) -> ScanResult {
+    let mut iter = PacketIter::with_options(Reader::new(input), options);
+    let mut scan = ScanResult {
+        packets: 0,
+        result: Ok(()),
+        offset: 0,
+    };
+    loop {
+        match iter.next() {
+            Some(Ok(_)) => {
+                scan.packets += 1;
+                scan.offset = iter.reader.offset();
+            }
+            None => return scan,
+            Some(Err(e)) => {
+                scan.result = Err(e);
+                return scan;
+            }
+        }
+    }
+}
+
+/// Same as [`next`], except that instead of a [`Packet`], it returns the tag
+/// and the range of the packet body.  The range is relative to the start of
+/// the data `reader` was created from (see [`Reader::offset`]), so the caller
+/// can slice the original buffer with it.
+///
+/// ```rust
+/// # use openpgp_parser::{Reader, packet::next_span};
+/// let data = [0xC2, 0, 0x88, 2, b'a', b'b'];
+/// let mut reader = Reader::new(&data);
+/// assert_eq!(next_span(&mut reader).unwrap(), Some((2, 2..2)));
+/// let (tag, range) = next_span(&mut reader).unwrap().unwrap();
+/// assert_eq!((tag, &data[range]), (2, &b"ab"[..]));
+/// assert!(next_span(&mut reader).unwrap().is_none());
+/// ```
+pub fn next_span(reader: &mut Reader) -> Result<Option<(u8, core::ops::Range<usize>)>, Error> {
+    Ok(skip_packet(reader)?.map(|(tag, len)| {
+        let end = reader.offset();
+        (tag, end - len..end)
+    }))
+}
+
+/// An iterator over the packets in a [`Reader`].
+///
+/// The iterator yields each packet in turn, and ends when the reader is
+/// exhausted.  If an error occurs, it is yielded once, after which the
+/// iterator always returns [`None`].
+///
+/// ```rust
+/// # use openpgp_parser::{Reader, packet::PacketIter};
+/// let mut iter = PacketIter::new(Reader::new(&[0xC2, 0, 0xC2, 1, 0, 0x00]));
+/// assert_eq!(iter.next().unwrap().unwrap().tag(), 2);
+/// assert_eq!(iter.next().unwrap().unwrap().contents(), &[0]);
+/// assert!(iter.next().unwrap().is_err());
+/// assert!(iter.next().is_none());
+/// ```
+#[derive(Clone, Debug)]
+pub struct PacketIter<'a> {
+    reader: Reader<'a>,
+    done: bool,
+    /// The number of packets that may still be read, if limited
+    remaining: Option<usize>,
+}
+
+impl<'a> PacketIter<'a> {
+    /// Creates a [`PacketIter`] that reads packets from `reader`
+    pub fn new(reader: Reader<'a>) -> Self {
+        Self::with_options(reader, &ParseOptions::default())
+    }
+
+    /// Creates a [`PacketIter`] that reads packets from `reader`, yielding
+    /// [`Error::TooManyPackets`] if there are more than
+    /// [`ParseOptions::max_packets`] of them.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Error, ParseOptions, Reader, packet::PacketIter};
+    /// let options = ParseOptions { max_packets: Some(1), ..ParseOptions::default() };
+    /// let mut iter = PacketIter::with_options(Reader::new(&[0xC2, 0, 0xC2, 0]), &options);
+    /// assert!(iter.next().unwrap().is_ok());
+    /// assert_eq!(iter.next(), Some(Err(Error::TooManyPackets)));
+    /// assert!(iter.next().is_none());
+    /// ```
+    pub fn with_options(reader: Reader<'a>, options: &ParseOptions) -> Self {
+        Self {
+            reader,
+            done: false,
+            remaining: options.max_packets,
+        }
+    }
+
+    /// Only yields packets with tag `tag`.  Errors are still yielded, and end
+    /// the iteration as usual.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::PacketIter};
+    /// let data = [0xC2, 1, 5, 0xCD, 0, 0xC2, 1, 6, 0x00];
+    /// let mut iter = PacketIter::new(Reader::new(&data)).of_tag(2);
+    /// assert_eq!(iter.next().unwrap().unwrap().contents(), &[5]);
+    /// assert_eq!(iter.next().unwrap().unwrap().contents(), &[6]);
+    /// assert!(iter.next().unwrap().is_err());
+    /// assert!(iter.next().is_none());
+    /// ```
+    pub fn of_tag(self, tag: u8) -> OfTag<'a> {
+        OfTag { inner: self, tag }
+    }
+}
+
+/// An iterator over the packets with a given tag, created by
+/// [`PacketIter::of_tag`]
+#[derive(Clone, Debug)]
+pub struct OfTag<'a> {
+    inner: PacketIter<'a>,
+    tag: u8,
+}
+
+impl<'a> Iterator for OfTag<'a> {
+    type Item = Result<Packet<'a>, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let tag = self.tag;
+        self.inner.find(|packet| match *packet {
+            Ok(ref packet) => packet.tag() == tag,
+            Err(_) => true,
+        })
+    }
+}
+
+impl<'a> core::iter::FusedIterator for OfTag<'a> {}
+
+impl<'a> Iterator for PacketIter<'a> {
+    type Item = Result<Packet<'a>, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.remaining == Some(0) && !self.reader.is_empty() {
+            self.done = true;
+            return Some(Err(Error::TooManyPackets));
         }
+        match next(&mut self.reader) {
+            Ok(Some(packet)) => {
+                self.remaining = self.remaining.map(|n| n - 1);
+                Some(Ok(packet))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<'a> core::iter::FusedIterator for PacketIter<'a> {}
+
+/// Read a packet from `reader`, reassembling a partial-length body into
+/// `scratch` if necessary.  Otherwise, this function is the same as [`next`],
+/// which always rejects partial-length packets.
+///
+/// Partial lengths are only accepted for the data packets that RFC 4880
+/// allows them on (compressed data, symmetrically encrypted data, literal
+/// data, and symmetrically encrypted integrity protected data), and the first
+/// partial body must be at least 512 bytes long.  Otherwise,
+/// [`Error::PartialLength`] is returned.
+///
+/// If the body was reassembled, the returned packet borrows from `scratch`.
+/// Any previous contents of `scratch` are discarded.
+#[cfg(feature = "partial-length")]
+pub fn next_partial<'a: 'b, 'b>(
+    reader: &mut Reader<'a>,
+    scratch: &'b mut alloc::vec::Vec<u8>,
+) -> Result<Option<Packet<'b>>, Error> {
+    let mut dup = *reader;
+    let (tagbyte, mut keybyte) = match (dup.maybe_byte(), dup.maybe_byte()) {
+        (Some(tagbyte @ 0xC0...0xFF), Some(keybyte @ 224...254)) => (tagbyte, keybyte),
+        _ => return next(reader),
     };
-    if packet.tag != 0 {
-        Ok(Some(packet))
-    } else {
-        Err(Error::BadTag)
+    let tag = tagbyte & 0x3F;
+    match tag {
+        8 | 9 | 11 | 18 => {}
+        _ => return Err(Error::PartialLength),
     }
+    if 1usize << (keybyte & 0x1F) < 512 {
+        return Err(Error::PartialLength);
+    }
+    scratch.clear();
+    while let 224...254 = keybyte {
+        scratch.extend_from_slice(dup.get_bytes(1usize << (keybyte & 0x1F))?);
+        keybyte = dup.byte()?;
+    }
+    scratch.extend_from_slice(get_varlen_body(keybyte, &mut dup)?);
+    *reader = dup;
+    Ok(Some(Packet {
+        tag,
+        format: Format::New,
+        buffer: &scratch[..],
+        raw: None,
+    }))
 }
 
 impl<'a> Packet<'a> {
+    /// Creates a new-format packet with tag `tag` and contents `body`, which
+    /// can then be serialized with [`Self::serialize`].  Returns
+    /// [`Error::InvalidTag`] if `tag` is 0 or greater than 63, and
+    /// [`Error::PacketTooLarge`] if `body` is longer than the largest length
+    /// a packet header can hold (`u32::MAX` bytes).
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Error, packet::{Format, Packet}};
+    /// let packet = Packet::new(13, b"a").unwrap();
+    /// assert_eq!((packet.tag(), packet.format(), packet.contents()), (13, Format::New, &b"a"[..]));
+    /// assert_eq!(Packet::new(0, b"a").unwrap_err(), Error::InvalidTag(0));
+    /// assert_eq!(Packet::new(64, b"a").unwrap_err(), Error::InvalidTag(64));
+    /// ```
+    pub fn new(tag: u8, body: &'a [u8]) -> Result<Self, Error> {
+        match tag {
+            1...63 => {}
+            _ => return Err(Error::InvalidTag(tag)),
+        }
+        check_serializable_len(body.len())?;
+        Ok(Packet {
+            tag,
+            format: Format::New,
+            buffer: body,
+            raw: None,
+        })
+    }
+
     /// Retrieves the packet’s tag.  Will always return non-zero.
     pub fn tag(&self) -> u8 {
         self.tag & 0x3F
     }
 
+    /// Retrieves the packet’s tag as a [`PacketType`]
+    pub fn typed_tag(&self) -> PacketType {
+        PacketType::from_u8(self.tag())
+    }
+
+    /// Retrieves the format the packet was encoded in.  Note that
+    /// [`Self::serialize`] always emits new-format packets, regardless of the
+    /// value returned here.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
     /// Retrieves the packet’s contents as a slice.
     pub fn contents(&self) -> &'a [u8] {
         self.buffer
     }
 
+    /// Retrieves the packet exactly as it was read, including the header.
+    /// Unlike [`Self::serialize`], this preserves the original framing, even if
+    /// it used an old-format or non-minimal length.
+    ///
+    /// Returns [`None`] if the packet was not read directly from a buffer:
+    /// packets created by [`Self::new`], reassembled partial-length packets,
+    /// and packets read from a `std::io::Read` stream.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::{next, Packet}};
+    /// let data = [0x88, 1, 5, 0xC2, 0xFF, 0, 0, 0, 1, 6];
+    /// let mut reader = Reader::new(&data);
+    /// assert_eq!(next(&mut reader).unwrap().unwrap().raw_bytes(), Some(&data[..3]));
+    /// assert_eq!(next(&mut reader).unwrap().unwrap().raw_bytes(), Some(&data[3..]));
+    /// assert_eq!(Packet::new(2, &[5]).unwrap().raw_bytes(), None);
+    /// ```
+    pub fn raw_bytes(&self) -> Option<&'a [u8]> {
+        self.raw
+    }
+
+    /// Returns a [`Reader`] over the packet’s contents, positioned at the
+    /// start of the body.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next};
+    /// let packet = next(&mut Reader::new(&[0xC2, 2, 4, 5])).unwrap().unwrap();
+    /// let mut body = packet.body_reader();
+    /// assert_eq!(body.offset(), 0);
+    /// assert_eq!(body.be_u16().unwrap(), 0x405);
+    /// assert!(body.is_empty());
+    /// ```
+    pub fn body_reader(&self) -> Reader<'a> {
+        Reader::new(self.buffer)
+    }
+
+    /// The length of the output of [`Self::serialize`]: the length of the body
+    /// plus a 2, 3, or 6 byte header, depending on the length of the body.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next};
+    /// let packet = next(&mut Reader::new(&[0x88, 1, 5])).unwrap().unwrap();
+    /// assert_eq!(packet.serialized_len(), 3);
+    /// ```
+    pub fn serialized_len(&self) -> usize {
+        let len = self.buffer.len();
+        len + new_format_header(self.tag, len).1
+    }
+
+    /// Whether the packet was read with the same length encoding that
+    /// [`Self::serialize`] would use
+    fn has_minimal_length(&self) -> bool {
+        self.raw.map(<[u8]>::len) == Some(self.serialized_len())
+    }
+
     /// Wraps the packet in OpenPGP encapsulation
     #[cfg(feature = "alloc")]
     pub fn serialize(&self) -> alloc::vec::Vec<u8> {
+        let mut v = alloc::vec::Vec::new();
+        self.serialize_into(&mut v);
+        v
+    }
+
+    /// Re-encodes the packet in canonical form: new format, with the shortest
+    /// length encoding for the size of the body.  The tag and body are
+    /// unchanged, whatever format the packet was originally in.
+    ///
+    /// This is currently the same as [`Self::serialize`], but unlike that
+    /// method, it is guaranteed to stay canonical: parsing the output with
+    /// [`next`] and canonicalizing the result produces the same bytes.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next};
+    /// let packet = next(&mut Reader::new(&[0x89, 0, 1, 5])).unwrap().unwrap();
+    /// assert_eq!(packet.canonicalize(), &[0xC2, 1, 5]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn canonicalize(&self) -> alloc::vec::Vec<u8> {
+        self.serialize()
+    }
+
+    /// Same as [`Self::serialize`], but appends the encapsulated packet to
+    /// `out` instead of allocating a new buffer.
+    #[cfg(feature = "alloc")]
+    pub fn serialize_into(&self, out: &mut alloc::vec::Vec<u8>) {
         let len = self.buffer.len();
         assert!(u64::from(u32::max_value()) >= len as u64);
-        let tag_byte = self.tag | 0b1100_0000u8;
-        let mut v = match len {
-            0...191 => {
-                // 1-byte
-                let mut v = alloc::vec::Vec::with_capacity(2 + len);
-                v.push(tag_byte);
-                v.push(len as u8);
-                v
-            }
-            192...8383 => {
-                // 2-byte
-                let mut v = alloc::vec::Vec::with_capacity(3 + len);
-                let len = len - 192;
-                v.push(tag_byte);
-                v.push((len >> 8) as u8 + 192);
-                v.push(len as u8);
-                v
-            }
-            _ => {
-                // 5-byte
-                let mut v = alloc::vec::Vec::with_capacity(6 + len);
-                v.extend_from_slice(&[
-                    tag_byte,
-                    0xFF,
-                    (len >> 24) as u8,
-                    (len >> 16) as u8,
-                    (len >> 8) as u8,
-                    len as u8,
-                ]);
-                v
-            }
+        let (header, header_len) = new_format_header(self.tag, len);
+        out.reserve(header_len + len);
+        out.extend_from_slice(&header[..header_len]);
+        out.extend_from_slice(self.buffer);
+    }
+
+    /// Wraps the packet in old-format OpenPGP encapsulation, using the
+    /// smallest length field (1, 2, or 4 bytes) that can hold the length of
+    /// the body.  Returns [`None`] if the tag is greater than 15, as the old
+    /// format cannot represent such tags.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next};
+    /// let packet = next(&mut Reader::new(&[0xC2, 1, 5])).unwrap().unwrap();
+    /// assert_eq!(packet.serialize_old_format().unwrap(), &[0x88, 1, 5]);
+    /// let packet = next(&mut Reader::new(&[0xD1, 0])).unwrap().unwrap();
+    /// assert!(packet.serialize_old_format().is_none());
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn serialize_old_format(&self) -> Option<alloc::vec::Vec<u8>> {
+        if self.tag > 15 {
+            return None;
+        }
+        let len = self.buffer.len();
+        assert!(len as u64 <= 0xFFFF_FFFF);
+        let tag_byte = 0x80 | self.tag << 2;
+        let mut out = alloc::vec::Vec::with_capacity(5 + len);
+        match len {
+            0...0xFF => out.extend_from_slice(&[tag_byte, len as u8]),
+            0x100...0xFFFF => out.extend_from_slice(&[tag_byte | 1, (len >> 8) as u8, len as u8]),
+            _ => out.extend_from_slice(&[
+                tag_byte | 2,
+                (len >> 24) as u8,
+                (len >> 16) as u8,
+                (len >> 8) as u8,
+                len as u8,
+            ]),
+        }
+        out.extend_from_slice(self.buffer);
+        Some(out)
+    }
+
+    /// Describes the packet for debugging: a line with the tag, format, and
+    /// body length, followed by a hex and ASCII dump of the first
+    /// [`DEBUG_DUMP_BYTES`] bytes of the body, 16 bytes per line.  If the body
+    /// is longer, a final line says how many bytes were left out.  Every line
+    /// ends with a newline, and the output depends only on the tag, format,
+    /// and body.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next};
+    /// let packet = next(&mut Reader::new(&[0xCD, 4, b'a', b'b', 0, 0xFF])).unwrap().unwrap();
+    /// assert_eq!(
+    ///     packet.debug_dump(),
+    ///     "tag 13 (UserId), new format, 4 bytes\n\
+    ///      00000000  61 62 00 ff                                      |ab..|\n"
+    /// );
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn debug_dump(&self) -> alloc::string::String {
+        use core::fmt::Write;
+        let mut out = alloc::string::String::new();
+        let format = match self.format {
+            Format::Old => "old",
+            Format::New => "new",
         };
-        v.extend_from_slice(self.buffer);
-        v
+        let len = self.buffer.len();
+        let _ = writeln!(
+            out,
+            "tag {} ({:?}), {} format, {} byte{}",
+            self.tag,
+            self.typed_tag(),
+            format,
+            len,
+            if len == 1 { "" } else { "s" }
+        );
+        let shown = &self.buffer[..len.min(DEBUG_DUMP_BYTES)];
+        for (i, line) in shown.chunks(16).enumerate() {
+            let _ = write!(out, "{:08x} ", i * 16);
+            for &byte in line {
+                let _ = write!(out, " {:02x}", byte);
+            }
+            for _ in line.len()..16 {
+                out.push_str("   ");
+            }
+            out.push_str("  |");
+            for &byte in line {
+                out.push(match byte {
+                    0x20...0x7E => char::from(byte),
+                    _ => '.',
+                })
+            }
+            out.push_str("|\n");
+        }
+        if len > shown.len() {
+            let _ = writeln!(out, "... {} more bytes", len - shown.len());
+        }
+        out
     }
 }
 
+/// The number of body bytes shown by [`Packet::debug_dump`]
+pub const DEBUG_DUMP_BYTES: usize = 64;
+
 #[cfg(all(feature = "alloc", test))]
 mod tests {
     use super::*;
     fn serialize(tag: u8, buffer: &[u8]) -> alloc::vec::Vec<u8> {
-        Packet { tag, buffer }.serialize()
+        Packet {
+            tag,
+            format: Format::New,
+            buffer,
+            raw: None,
+        }
+        .serialize()
     }
+    fn assert_truncated<T: core::fmt::Debug>(result: Result<T, Error>) {
+        match result {
+            Err(Error::Truncated { .. }) => {}
+            other => panic!("expected truncated input, got {:?}", other),
+        }
+    }
+    #[test]
+    fn packet_type_mapping() {
+        for tag in 0..=255u8 {
+            let ty = PacketType::from_u8(tag);
+            assert_eq!(ty.to_u8(), tag);
+            match tag {
+                1...14 | 17...19 => assert_ne!(ty, PacketType::Unknown(tag)),
+                _ => assert_eq!(ty, PacketType::Unknown(tag)),
+            }
+        }
+        let mut reader = Reader::new(&[0xB4, 0, 0xCE, 0]);
+        assert_eq!(
+            next(&mut reader).unwrap().unwrap().typed_tag(),
+            PacketType::UserId
+        );
+        assert_eq!(
+            next(&mut reader).unwrap().unwrap().typed_tag(),
+            PacketType::PublicSubkey
+        );
+    }
+
+    #[test]
+    fn length_limit() {
+        let expected = |needed| {
+            if cfg!(target_pointer_width = "32") {
+                Error::PacketTooLarge
+            } else {
+                Error::Truncated {
+                    needed,
+                    available: 1,
+                }
+            }
+        };
+        let mut reader = Reader::new(&[0xC2, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0]);
+        assert_eq!(next(&mut reader).unwrap_err(), expected(0xFFFF_FFFF));
+        let mut reader = Reader::new(&[0xC2, 0xFF, 0x80, 0, 0, 0, 0]);
+        assert_eq!(next(&mut reader).unwrap_err(), expected(0x8000_0000));
+        // the largest length that every platform can address
+        let mut reader = Reader::new(&[0xC2, 0xFF, 0x7F, 0xFF, 0xFF, 0xFF, 0]);
+        assert_eq!(
+            next(&mut reader).unwrap_err(),
+            Error::Truncated {
+                needed: 0x7FFF_FFFF,
+                available: 1
+            }
+        );
+        // and so are old-format lengths
+        let mut reader = Reader::new(&[0x8A, 0xFF, 0xFF, 0xFF, 0xFF, 0]);
+        assert_eq!(next(&mut reader).unwrap_err(), expected(0xFFFF_FFFF));
+        let mut reader = Reader::new(&[0x8A, 0x80, 0, 0, 0, 0]);
+        assert_eq!(next(&mut reader).unwrap_err(), expected(0x8000_0000));
+        let mut reader = Reader::new(&[0x8A, 0x7F, 0xFF, 0xFF, 0xFF, 0]);
+        assert_eq!(
+            next(&mut reader).unwrap_err(),
+            Error::Truncated {
+                needed: 0x7FFF_FFFF,
+                available: 1
+            }
+        );
+        assert_eq!(body_len(0x7FFF_FFFF), Ok(0x7FFF_FFFF));
+        if cfg!(target_pointer_width = "32") {
+            assert_eq!(body_len(0x8000_0000), Err(Error::PacketTooLarge));
+        } else {
+            assert_eq!(body_len(0xFFFF_FFFF), Ok(0xFFFF_FFFF));
+        }
+        // subpacket lengths are checked the same way
+        let mut reader = Reader::new(&[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0]);
+        assert_eq!(
+            get_varlen_bytes(&mut reader).unwrap_err(),
+            expected(0xFFFF_FFFF)
+        );
+    }
+
+    #[test]
+    fn two_byte_lengths() {
+        let len = |first, second| get_varlen_len(first, &mut Reader::new(&[second]));
+        assert_eq!(len(192, 0), Ok(192));
+        assert_eq!(len(192, 255), Ok(447));
+        assert_eq!(len(193, 0), Ok(448));
+        assert_eq!(len(223, 0), Ok(8128));
+        assert_eq!(len(223, 255), Ok(8383));
+        // every length from 192 to 8383 has exactly one encoding, in order
+        let mut expected = 192;
+        for first in 192..224 {
+            for second in 0..256 {
+                assert_eq!(len(first as u8, second as u8), Ok(expected));
+                expected += 1;
+            }
+        }
+        assert_eq!(expected, 8384);
+        // the second byte is required
+        assert_eq!(
+            get_varlen_len(223, &mut Reader::empty()),
+            Err(Error::Truncated {
+                needed: 1,
+                available: 0
+            })
+        );
+        let mut body = vec![223, 255];
+        body.resize(2 + 8383, 0);
+        assert_eq!(
+            get_varlen_bytes(&mut Reader::new(&body)).unwrap().len(),
+            8383
+        );
+        let mut reader = Reader::new(&body[..body.len() - 1]);
+        assert_eq!(
+            get_varlen_bytes(&mut reader),
+            Err(Error::Truncated {
+                needed: 8383,
+                available: 8382
+            })
+        );
+    }
+
+    #[test]
+    fn of_tag() {
+        static KEY: &[u8] = include_bytes!("../../data/ed25519-key.gpg");
+        let sigs: alloc::vec::Vec<_> = PacketIter::new(Reader::new(KEY))
+            .of_tag(2)
+            .map(|packet| packet.unwrap().raw_bytes().unwrap())
+            .collect();
+        assert_eq!(sigs, [&KEY[82..228], &KEY[286..408]]);
+        assert_eq!(PacketIter::new(Reader::new(KEY)).of_tag(13).count(), 1);
+        assert_eq!(PacketIter::new(Reader::new(KEY)).of_tag(3).count(), 0);
+        // errors are yielded even if no packet has the tag
+        let mut iter = PacketIter::new(Reader::new(&KEY[..100])).of_tag(3);
+        assert_eq!(
+            iter.next(),
+            Some(Err(Error::Truncated {
+                needed: 144,
+                available: 16
+            }))
+        );
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn packet_equality() {
+        use std::collections::HashSet;
+        let data = [0xC2, 1, 5, 0x88, 1, 5, 0xC2, 1, 5, 0xC2, 1, 6];
+        let packets = PacketIter::new(Reader::new(&data))
+            .collect::<Result<alloc::vec::Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(packets[0], packets[2]);
+        // same tag and contents, different format
+        assert_ne!(packets[0], packets[1]);
+        assert_ne!(packets[0], packets[3]);
+        let unique: HashSet<_> = packets.iter().collect();
+        assert_eq!(unique.len(), 3);
+    }
+
+    #[test]
+    fn raw_bytes() {
+        static KEY: &[u8] = include_bytes!("../../data/ed25519-key.gpg");
+        let mut reader = Reader::new(KEY);
+        let mut raw = alloc::vec::Vec::new();
+        while let Some(packet) = next(&mut reader).unwrap() {
+            let bytes = packet.raw_bytes().unwrap();
+            assert!(bytes.ends_with(packet.contents()));
+            raw.extend_from_slice(bytes);
+        }
+        assert_eq!(raw, KEY);
+        // equality ignores the framing
+        let data = [0xC2, 1, 5, 0xC2, 0xFF, 0, 0, 0, 1, 5];
+        let mut reader = Reader::new(&data);
+        let (first, second) = (
+            next(&mut reader).unwrap().unwrap(),
+            next(&mut reader).unwrap().unwrap(),
+        );
+        assert_eq!(first, second);
+        assert_eq!(first, Packet::new(2, &[5]).unwrap());
+        assert_ne!(first.raw_bytes(), second.raw_bytes());
+    }
+
+    #[test]
+    fn packet_iter() {
+        let data = [0xC2, 0, 0x88, 1, b'a', 0xC2];
+        let packets = PacketIter::new(Reader::new(&data[..4]))
+            .collect::<Result<alloc::vec::Vec<_>, _>>()
+            .unwrap_err();
+        assert_eq!(
+            packets,
+            Error::Truncated {
+                needed: 1,
+                available: 0
+            }
+        );
+        let packets = PacketIter::new(Reader::new(&data[..5]))
+            .collect::<Result<alloc::vec::Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(packets.len(), 2);
+        assert_eq!((packets[0].tag(), packets[0].contents()), (2, &[][..]));
+        assert_eq!((packets[1].tag(), packets[1].contents()), (2, &b"a"[..]));
+        let mut iter = PacketIter::new(Reader::new(&data[..]));
+        assert!(iter.next().unwrap().is_ok());
+        assert!(iter.next().unwrap().is_ok());
+        assert_eq!(
+            iter.next().unwrap().unwrap_err(),
+            Error::Truncated {
+                needed: 1,
+                available: 0
+            }
+        );
+        for _ in 0..3 {
+            assert!(iter.next().is_none());
+        }
+        assert!(PacketIter::new(Reader::empty()).next().is_none());
+    }
+
+    #[test]
+    fn packet_too_large() {
+        // old format, 4-byte length
+        let mut reader = Reader::new(&[0x8A, 0xFF, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(
+            next_with_limit(&mut reader, 0xFFFF_FFFE).unwrap_err(),
+            Error::PacketTooLarge
+        );
+        assert_eq!(
+            next_with_limit(&mut Reader::new(&[0x88, 1, 0]), 0).unwrap_err(),
+            Error::PacketTooLarge
+        );
+        // new format, 2-byte length
+        let mut data = vec![0xC2, 0xC0, 0x00];
+        data.resize(195, 0);
+        assert_eq!(
+            next_with_limit(&mut Reader::new(&data), 191).unwrap_err(),
+            Error::PacketTooLarge
+        );
+        let packet = next_with_limit(&mut Reader::new(&data), 192)
+            .unwrap()
+            .unwrap();
+        assert_eq!(packet.contents().len(), 192);
+    }
+
+    #[test]
+    fn partial_first_length_byte() {
+        for &keybyte in &[224, 0xE9, 254] {
+            assert_eq!(
+                next(&mut Reader::new(&[0xCB, keybyte, 0])).unwrap_err(),
+                Error::PartialLengthFirstByte
+            );
+        }
+        // truncated, not partial
+        assert_eq!(
+            next(&mut Reader::new(&[0xCB, 223])).unwrap_err(),
+            Error::Truncated {
+                needed: 1,
+                available: 0
+            }
+        );
+    }
+
+    #[test]
+    fn scan_all_never_panics() {
+        let key = include_bytes!("../../data/ed25519-key.gpg");
+        for len in 0..key.len() {
+            let scan = scan_all(&key[..len]);
+            assert!(scan.offset <= len);
+            assert_eq!(scan.result.is_ok(), scan.offset == len);
+        }
+        assert_eq!(count_packets(&mut Reader::new(key)), Ok(5));
+        assert_eq!(
+            count_packets(&mut Reader::new(&key[..100])),
+            Err(Error::Truncated {
+                needed: 144,
+                available: 16
+            })
+        );
+        let histogram = tag_histogram(&mut Reader::new(key)).unwrap();
+        let mut expected = [0u32; 64];
+        expected[2] = 2;
+        expected[6] = 1;
+        expected[13] = 1;
+        expected[14] = 1;
+        assert_eq!(histogram[..], expected[..]);
+        assert_eq!(
+            tag_histogram(&mut Reader::new(&key[..100])),
+            Err(Error::Truncated {
+                needed: 144,
+                available: 16
+            })
+        );
+        assert_eq!(
+            tag_histogram(&mut Reader::new(&[0xFF, 0, 0x80, 0])),
+            Err(Error::BadTag(0x80))
+        );
+        assert_eq!(
+            scan_all(key),
+            ScanResult {
+                packets: 5,
+                result: Ok(()),
+                offset: key.len(),
+            }
+        );
+        // Pseudo-random input from a linear congruential generator
+        let mut state = 1u32;
+        let mut buf = [0u8; 64];
+        for _ in 0..10000 {
+            for byte in buf.iter_mut() {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                *byte = (state >> 16) as u8;
+            }
+            let scan = scan_all(&buf);
+            assert!(scan.offset <= buf.len());
+        }
+    }
+
+    #[test]
+    fn max_packets() {
+        let key = include_bytes!("../../data/ed25519-key.gpg");
+        for max in 0..7 {
+            let options = ParseOptions {
+                max_packets: Some(max),
+                ..ParseOptions::default()
+            };
+            let results: Vec<_> = PacketIter::with_options(Reader::new(key), &options).collect();
+            let scan = scan_all_with_options(key, &options);
+            if max >= 5 {
+                assert_eq!(results.len(), 5);
+                assert!(results.iter().all(Result::is_ok));
+                assert_eq!(scan, scan_all(key));
+            } else {
+                assert_eq!(results.len(), max + 1);
+                assert_eq!(results[max], Err(Error::TooManyPackets));
+                assert_eq!(scan.packets, max);
+                assert_eq!(scan.result, Err(Error::TooManyPackets));
+                let offsets = [0, 53, 82, 228, 286];
+                assert_eq!(scan.offset, offsets[max]);
+            }
+        }
+        // the limit does not apply to empty input
+        let options = ParseOptions {
+            max_packets: Some(0),
+            ..ParseOptions::default()
+        };
+        assert!(PacketIter::with_options(Reader::empty(), &options)
+            .next()
+            .is_none());
+        // the default is unlimited
+        let many = [0xC2, 0].repeat(10000);
+        assert_eq!(PacketIter::new(Reader::new(&many)).count(), 10000);
+    }
+
+    #[test]
+    fn packet_span() {
+        let mut data = vec![0xC2, 0xC0, 0];
+        data.extend((0..192).map(|i| i as u8));
+        data.extend_from_slice(&[0xC2, 1]);
+        let mut reader = Reader::new(&data);
+        assert_eq!(next_span(&mut reader).unwrap(), Some((2, 3..195)));
+        assert_eq!(
+            next_span(&mut reader).unwrap_err(),
+            Error::Truncated {
+                needed: 1,
+                available: 0
+            }
+        );
+        let mut reader = Reader::new(&data[195..]);
+        assert_eq!(
+            next_span(&mut reader).unwrap_err(),
+            Error::Truncated {
+                needed: 1,
+                available: 0
+            }
+        );
+        let mut reader = Reader::new(&data[..195]);
+        reader.get_bytes(3).unwrap();
+        assert_eq!(
+            next_span(&mut reader).unwrap_err(),
+            Error::PacketFirstBitZero
+        );
+    }
+
+    #[cfg(feature = "partial-length")]
+    #[test]
+    fn partial_length() {
+        let mut data = vec![0xCB, 0xE9];
+        data.extend((0..512).map(|i| i as u8));
+        data.extend_from_slice(&[0xE0, 0xAA, 0xE1, 0xBB, 0xCC, 0x01, 0xDD]);
+        let mut expected: alloc::vec::Vec<u8> = (0..512).map(|i| i as u8).collect();
+        expected.extend_from_slice(&[0xAA, 0xBB, 0xCC, 0xDD]);
+        data.extend_from_slice(&[0xC2, 0x01, 0x10]);
+        // `next` still rejects partial lengths, reporting the first length byte
+        assert_eq!(
+            next(&mut Reader::new(&data)).unwrap_err(),
+            Error::PartialLengthFirstByte
+        );
+        let mut reader = Reader::new(&data);
+        let mut scratch = vec![1, 2, 3];
+        {
+            let packet = next_partial(&mut reader, &mut scratch).unwrap().unwrap();
+            assert_eq!(packet.tag(), 11);
+            assert_eq!(packet.format(), Format::New);
+            assert_eq!(packet.contents(), &expected[..]);
+        }
+        let packet = next_partial(&mut reader, &mut scratch).unwrap().unwrap();
+        assert_eq!((packet.tag(), packet.contents()), (2, &[0x10][..]));
+        assert!(next_partial(&mut reader, &mut scratch).unwrap().is_none());
+        // truncated in the middle of a chunk or of a length header
+        for i in 2..data.len() - 4 {
+            let mut reader = Reader::new(&data[..i]);
+            assert_truncated(next_partial(&mut reader, &mut scratch));
+            assert_eq!(reader.len(), i);
+        }
+        // first chunk too short
+        data[1] = 0xE8;
+        assert_eq!(
+            next_partial(&mut Reader::new(&data), &mut scratch).unwrap_err(),
+            Error::PartialLength
+        );
+        // not a data packet
+        data[0] = 0xC2;
+        data[1] = 0xE9;
+        assert_eq!(
+            next_partial(&mut Reader::new(&data), &mut scratch).unwrap_err(),
+            Error::PartialLength
+        );
+    }
+
+    #[test]
+    fn canonicalize_idempotent() {
+        let body = vec![0x55u8; 8384];
+        for &len in &[0, 1, 191, 192, 8383, 8384] {
+            let mut old = vec![0x8A];
+            old.extend_from_slice(&(len as u32).to_be_bytes());
+            old.extend_from_slice(&body[..len]);
+            let canonical = next(&mut Reader::new(&old))
+                .unwrap()
+                .unwrap()
+                .canonicalize();
+            let mut reader = Reader::new(&canonical);
+            let packet = next(&mut reader).unwrap().unwrap();
+            assert!(reader.is_empty());
+            assert_eq!(packet.format(), Format::New);
+            assert_eq!((packet.tag(), packet.contents()), (2, &body[..len]));
+            assert_eq!(packet.canonicalize(), canonical);
+        }
+    }
+
+    #[test]
+    fn old_format_round_trip() {
+        let body = vec![0xAAu8; 0x10000];
+        for tag in 1..16 {
+            for &(len, header_len) in &[(0, 2), (0xFF, 2), (0x100, 3), (0xFFFF, 3), (0x10000, 5)] {
+                let packet = Packet {
+                    tag,
+                    format: Format::New,
+                    buffer: &body[..len],
+                    raw: None,
+                };
+                let old = packet.serialize_old_format().unwrap();
+                assert_eq!(old.len(), header_len + len);
+                let mut reader = Reader::new(&old);
+                let parsed = next(&mut reader).unwrap().unwrap();
+                assert!(reader.is_empty());
+                assert_eq!(parsed.format(), Format::Old);
+                assert_eq!((parsed.tag(), parsed.contents()), (tag, &body[..len]));
+            }
+        }
+        for tag in 16..64 {
+            let packet = Packet {
+                tag,
+                format: Format::New,
+                buffer: &[],
+                raw: None,
+            };
+            assert!(packet.serialize_old_format().is_none());
+        }
+    }
+
+    #[test]
+    fn strict_lengths() {
+        let body = [0u8; 8384];
+        for &len in &[0, 191, 192, 8383, 8384] {
+            let canonical = serialize(2, &body[..len]);
+            let packet = next_strict(&mut Reader::new(&canonical)).unwrap().unwrap();
+            assert_eq!(packet.contents().len(), len);
+            let mut five_byte = vec![0xC2, 0xFF];
+            five_byte.extend_from_slice(&(len as u32).to_be_bytes());
+            five_byte.extend_from_slice(&body[..len]);
+            let lenient = next(&mut Reader::new(&five_byte)).unwrap().unwrap();
+            assert_eq!(lenient, packet);
+            let strict = next_strict(&mut Reader::new(&five_byte));
+            if len < 8384 {
+                assert_eq!(strict, Err(Error::NonCanonicalLength));
+            } else {
+                assert_eq!(strict, Ok(Some(packet)));
+            }
+        }
+        // 192 is the smallest length with a 2-byte encoding
+        assert_eq!(serialize(2, &body[..191]).len(), 191 + 2);
+        assert_eq!(serialize(2, &body[..192])[1..3], [192, 0]);
+        assert_eq!(serialize(2, &body[..8383])[1..3], [223, 255]);
+        // old-format packets are not checked
+        let old = [0x8A, 0, 0, 0, 1, 5];
+        assert!(next_strict(&mut Reader::new(&old)).unwrap().is_some());
+    }
+
+    #[test]
+    fn header_boundaries() {
+        for &(len, header_len) in &[
+            (0, 2),
+            (191, 2),
+            (192, 3),
+            (8383, 3),
+            (8384, 6),
+            (0xFFFF, 6),
+            (0x1_0000, 6),
+            (0x7FFF_FFFF, 6),
+            (0xFFFF_FFFF, 6),
+        ] {
+            let (header, used) = new_format_header(2, len);
+            assert_eq!(used, header_len);
+            assert_eq!(header[0], 0xC2);
+            assert!(header[1] < 224 || header[1] == 255);
+            assert!(header[used..].iter().all(|&b| b == 0));
+            let mut reader = Reader::new(&header[..used]);
+            match read_header(&mut reader) {
+                Ok(Some((0xC2, 2, Format::New, parsed))) => assert_eq!(parsed, len),
+                Err(Error::PacketTooLarge) => {
+                    assert!(cfg!(target_pointer_width = "32") && len > 0x7FFF_FFFF)
+                }
+                other => panic!("bad header for length {}: {:?}", len, other),
+            }
+            assert!(reader.is_empty());
+        }
+        // the largest 2-byte length and the smallest 5-byte one
+        assert_eq!(new_format_header(2, 8383).0[1..3], [223, 255]);
+        assert_eq!(new_format_header(2, 8384).0[1..6], [255, 0, 0, 0x20, 0xC0]);
+        assert_eq!(new_format_header(2, 0xFFFF_FFFF).0[1..6], [255; 5]);
+    }
+
+    #[test]
+    fn is_canonical_matches_serialize() {
+        static KEY: &[u8] = include_bytes!("../../data/ed25519-key.gpg");
+        let reserialize = |input: &[u8]| {
+            let mut out = alloc::vec::Vec::new();
+            for packet in PacketIter::new(Reader::new(input)) {
+                packet.unwrap().serialize_into(&mut out)
+            }
+            out
+        };
+        let canonical = reserialize(KEY);
+        assert_eq!(is_canonical(&canonical), Ok(true));
+        assert_eq!(reserialize(&canonical), canonical);
+        // the key uses old-format packets
+        assert_eq!(is_canonical(KEY), Ok(KEY == &canonical[..]));
+        assert_ne!(KEY, &canonical[..]);
+        // non-minimal lengths
+        let mut long = vec![0xC2, 0xFF, 0, 0, 0, 1, 5];
+        assert_eq!(is_canonical(&long), Ok(false));
+        long.splice(..6, [0xC2, 1].iter().cloned());
+        assert_eq!(is_canonical(&long), Ok(true));
+        assert_eq!(
+            is_canonical(&[0xC2, 1, 5, 0]),
+            Err(Error::PacketFirstBitZero)
+        );
+    }
+
+    #[test]
+    fn new_packet_round_trip() {
+        let body = [0x55u8; 300];
+        for tag in 1..64 {
+            let packet = Packet::new(tag, &body[..tag as usize * 4]).unwrap();
+            let serialized = packet.serialize();
+            assert_eq!(parse_single(&mut Reader::new(&serialized)), Ok(packet));
+        }
+        for &tag in &[0, 64, 0x80, 0xFF] {
+            assert_eq!(Packet::new(tag, &[]), Err(Error::InvalidTag(tag)));
+        }
+        assert_eq!(check_serializable_len(0xFFFF_FFFF), Ok(()));
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(check_serializable_len(1 << 32), Err(Error::PacketTooLarge));
+    }
+
+    #[test]
+    fn serialize_into_appends() {
+        let mut out = vec![0xAA];
+        let data = [0x55u8; 200];
+        let short = Packet {
+            tag: 2,
+            format: Format::New,
+            buffer: &data[..1],
+            raw: None,
+        };
+        let long = Packet {
+            tag: 6,
+            format: Format::Old,
+            buffer: &data[..],
+            raw: None,
+        };
+        short.serialize_into(&mut out);
+        long.serialize_into(&mut out);
+        let mut expected = vec![0xAA];
+        expected.extend_from_slice(&short.serialize());
+        expected.extend_from_slice(&long.serialize());
+        assert_eq!(out, expected);
+        assert_eq!(out[..4], [0xAA, 0xC2, 1, 0x55]);
+        assert_eq!(out[4..7], [0xC6, 192, 8]);
+        assert_eq!(out.len(), 207);
+    }
+
+    #[test]
+    fn debug_dump() {
+        let packet = next(&mut Reader::new(&[0x88, 1, 0x41])).unwrap().unwrap();
+        assert_eq!(
+            packet.debug_dump(),
+            "tag 2 (Signature), old format, 1 byte\n\
+             00000000  41                                               |A|\n"
+        );
+        assert_eq!(
+            Packet::new(60, &[]).unwrap().debug_dump(),
+            "tag 60 (Unknown(60)), new format, 0 bytes\n"
+        );
+        let body: Vec<u8> = (0..100).map(|i| i as u8 + 0x30).collect();
+        let dump = Packet::new(11, &body).unwrap().debug_dump();
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(lines[0], "tag 11 (LiteralData), new format, 100 bytes");
+        assert_eq!(
+            lines[1],
+            "00000000  30 31 32 33 34 35 36 37 38 39 3a 3b 3c 3d 3e 3f  |0123456789:;<=>?|"
+        );
+        assert_eq!(
+            lines[4],
+            "00000030  60 61 62 63 64 65 66 67 68 69 6a 6b 6c 6d 6e 6f  |`abcdefghijklmno|"
+        );
+        assert_eq!(lines[5], "... 36 more bytes");
+        assert!(dump.ends_with('\n'));
+        assert_eq!(dump, Packet::new(11, &body).unwrap().debug_dump());
+    }
+
     #[test]
     fn check_packet_serialization_short() {
         assert_eq!(serialize(0x4F, &[][..]), vec![0b1100_1111, 0x0]);
@@ -143,12 +1575,17 @@ mod tests {
     #[test]
     fn old_format_parsing() {
         let mut buffer = vec![0u8; (1usize << 28) + 5];
+        for &tagbyte in &[0x80, 0x81, 0x82] {
+            buffer[0] = tagbyte;
+            let mut reader = Reader::new(&buffer[..5]);
+            assert_eq!(next(&mut reader).unwrap_err(), Error::BadTag(tagbyte));
+        }
         for tag in 1..16 {
             buffer[0] = 0x81 | tag << 2;
             buffer[1] = 0;
             {
                 let mut reader = Reader::new(&buffer[..1]);
-                assert_eq!(next(&mut reader).unwrap_err(), Error::PrematureEOF);
+                assert_truncated(next(&mut reader));
             }
             for len in 0..256 {
                 buffer[0] = 0x80 | tag << 2;
@@ -157,9 +1594,10 @@ mod tests {
                 let mut packet = next(&mut reader).unwrap().unwrap();
                 assert_eq!(packet.buffer.len(), len);
                 assert_eq!(packet.tag, tag);
+                assert_eq!(packet.format(), Format::Old);
                 assert_eq!(reader.len(), 0);
                 reader = Reader::new(&buffer[..len + 1]);
-                assert_eq!(next(&mut reader).unwrap_err(), Error::PrematureEOF);
+                assert_truncated(next(&mut reader));
                 reader = Reader::new(&buffer[..len + 3]);
                 packet = next(&mut reader).unwrap().unwrap();
                 assert_eq!(packet.buffer.len(), len);
@@ -171,7 +1609,7 @@ mod tests {
             buffer[2] = 0;
             for i in 1..3 {
                 let mut reader = Reader::new(&buffer[..i]);
-                assert_eq!(next(&mut reader).unwrap_err(), Error::PrematureEOF);
+                assert_truncated(next(&mut reader));
             }
             for len in 0..65536 {
                 buffer[1] = (len >> 8) as _;
@@ -182,7 +1620,7 @@ mod tests {
                 assert_eq!(packet.tag, tag);
                 assert_eq!(reader.len(), 0);
                 reader = Reader::new(&buffer[..len + 2]);
-                assert_eq!(next(&mut reader).unwrap_err(), Error::PrematureEOF);
+                assert_truncated(next(&mut reader));
                 reader = Reader::new(&buffer[..len + 4]);
                 packet = next(&mut reader).unwrap().unwrap();
                 assert_eq!(packet.buffer.len(), len);
@@ -203,7 +1641,7 @@ mod tests {
                 assert_eq!(packet.tag, tag);
                 assert_eq!(reader.len(), 0);
                 reader = Reader::new(&buffer[..len + 4]);
-                assert_eq!(next(&mut reader).unwrap_err(), Error::PrematureEOF);
+                assert_truncated(next(&mut reader));
                 reader = Reader::new(&buffer[..len + 6]);
                 let packet = next(&mut reader).unwrap().unwrap();
                 assert_eq!(packet.buffer.len(), len);
@@ -212,7 +1650,7 @@ mod tests {
             }
             buffer[0] = 0x83 | tag << 2;
             let mut reader = Reader::new(&buffer[..20]);
-            next(&mut reader).unwrap_err();
+            assert_eq!(next(&mut reader).unwrap_err(), Error::IndefiniteLength);
         }
     }
     #[test]
@@ -221,13 +1659,17 @@ mod tests {
         let buffer = vec![0u8; 65536];
         for tag in 1..64 {
             for j in 0..buffer.len() {
-                let serialized = Packet {
+                let packet = Packet {
                     tag,
+                    format: Format::New,
                     buffer: &buffer[..j],
-                }
-                .serialize();
+                    raw: None,
+                };
+                let serialized = packet.serialize();
+                assert_eq!(serialized.len(), packet.serialized_len());
                 assert_eq!(serialized[0] & 0b1100_0000, 0b1100_0000);
                 assert_eq!(serialized[0] & 0b0011_1111, tag);
+                assert!(serialized[1] < 224 || serialized[1] == 255);
                 if j < 192 {
                     assert_eq!(usize::from(serialized[1]), j);
                     assert_eq!(serialized.len(), j + 2);
@@ -245,7 +1687,7 @@ mod tests {
                     }
                     for k in 1..6 {
                         let mut short_reader = Reader::new(&serialized[..k]);
-                        assert_eq!(next(&mut short_reader).unwrap_err(), Error::PrematureEOF);
+                        assert_truncated(next(&mut short_reader));
                     }
                 } else {
                     assert_eq!(serialized.len(), j + 6);
@@ -259,20 +1701,23 @@ mod tests {
                     );
                     for k in 1..6 {
                         let mut short_reader = Reader::new(&serialized[..k]);
-                        assert_eq!(next(&mut short_reader).unwrap_err(), Error::PrematureEOF);
+                        assert_truncated(next(&mut short_reader));
                     }
                 }
                 {
                     let mut short_reader = Reader::new(&serialized[..serialized.len() - 1]);
-                    assert_eq!(next(&mut short_reader).unwrap_err(), Error::PrematureEOF);
+                    assert_truncated(next(&mut short_reader));
                 }
                 let mut reader = Reader::new(&serialized);
                 let Packet {
                     tag: deserialized_tag,
+                    format: deserialized_format,
                     buffer: deserialized_buffer,
+                    ..
                 } = next(&mut reader).unwrap().unwrap();
                 assert_eq!(reader.len(), 0);
                 assert_eq!(tag, deserialized_tag);
+                assert_eq!(deserialized_format, Format::New);
                 assert_eq!(&buffer[..j], deserialized_buffer);
             }
         }
@@ -0,0 +1,402 @@
+//! Minimal RPM header framing
+//!
+//! An RPM header starts with an 8-byte magic, followed by the number of index
+//! entries and the length of the data section, both as big-endian 32-bit
+//! integers.  Then come the index entries, 16 bytes each, and the data.  This
+//! module only checks that framing, which is enough to locate the signature
+//! tags.  Index entries are only checked when their data is requested, and
+//! then only to ensure that the data is within the data section; full
+//! validation is the job of `rpm-parser`.
+
+#[cfg(feature = "verify")]
+use super::hashing::{Digest, HashingReader};
+#[cfg(feature = "verify")]
+use super::key::PublicKey;
+use super::packet::parse_single;
+use super::signature::Signature;
+#[cfg(feature = "verify")]
+use super::verify::Verifier;
+use super::{Error, Reader};
+
+/// The magic at the start of every RPM header
+pub const HEADER_MAGIC: [u8; 8] = [0x8E, 0xAD, 0xE8, 0x01, 0, 0, 0, 0];
+
+/// A borrowed RPM header
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Header<'a> {
+    index: &'a [u8],
+    data: &'a [u8],
+}
+
+/// The signature tag holding an RSA signature of the header.  Despite the
+/// name, this is used for signatures with any public-key algorithm.
+///
+/// The signed data is the whole main header, exactly as it is stored in the
+/// package: from the first byte of its [`HEADER_MAGIC`] to the end of its data
+/// section, which is everything that [`Header::parse`] consumes.  The main
+/// header starts after the signature header, padded to a multiple of 8 bytes
+/// from the start of the package.  As usual for OpenPGP, the signature's
+/// hashed material and trailer are hashed after the header.  With the
+/// `verify` feature, `verify_rpm_header` checks the signature.
+pub const RPMSIGTAG_RSAHEADER: u32 = 268;
+/// The signature tag holding a signature of the header and payload
+pub const RPMSIGTAG_PGP: u32 = 1002;
+
+/// An RPM header index entry
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct IndexEntry<'a> {
+    /// The tag
+    pub tag: u32,
+    /// The type of the data
+    pub ty: u32,
+    /// The offset of the data in the data section
+    pub offset: u32,
+    /// The number of items in the data
+    pub count: u32,
+    header_data: &'a [u8],
+}
+
+impl<'a> IndexEntry<'a> {
+    /// Gets the data of this entry.  String types (6, 8, and 9) extend up to
+    /// and including the `count`th NUL byte; other types have a fixed size per
+    /// item.  Returns [`Error::CorruptHeader`] if the type is unknown or the
+    /// data would extend past the end of the data section.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Error, Reader, rpm::Header};
+    /// let mut reader = Reader::new(&[
+    ///     0x8E, 0xAD, 0xE8, 0x01, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 3,
+    ///     0, 0, 0, 1, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0, 1,
+    ///     0, 0, 0, 2, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 1,
+    ///     b'a', 0, 0,
+    /// ]);
+    /// let header = Header::parse(&mut reader).unwrap();
+    /// assert_eq!(header.entry(1).unwrap().data().unwrap(), b"a\0");
+    /// assert_eq!(header.entry(2).unwrap().data().unwrap_err(), Error::CorruptHeader);
+    /// assert!(header.entry(3).is_none());
+    /// ```
+    pub fn data(&self) -> Result<&'a [u8], Error> {
+        let rest = self
+            .header_data
+            .get(self.offset as usize..)
+            .ok_or(Error::CorruptHeader)?;
+        let count = self.count as usize;
+        let len = match self.ty {
+            // CHAR, INT8, BIN
+            1 | 2 | 7 => count,
+            // INT16
+            3 => count.checked_mul(2).ok_or(Error::CorruptHeader)?,
+            // INT32
+            4 => count.checked_mul(4).ok_or(Error::CorruptHeader)?,
+            // INT64
+            5 => count.checked_mul(8).ok_or(Error::CorruptHeader)?,
+            // STRING, STRING_ARRAY, I18NSTRING
+            6 | 8 | 9 => {
+                let mut nuls = 0;
+                match rest.iter().position(|&c| {
+                    nuls += usize::from(c == 0);
+                    nuls == count
+                }) {
+                    Some(i) if count != 0 => i + 1,
+                    _ => return Err(Error::CorruptHeader),
+                }
+            }
+            _ => return Err(Error::CorruptHeader),
+        };
+        rest.get(..len).ok_or(Error::CorruptHeader)
+    }
+}
+
+impl<'a> Header<'a> {
+    /// Parses an RPM header from `reader`.  Returns [`Error::BadHeaderMagic`]
+    /// if the magic is wrong, [`Error::CorruptHeader`] if the index size
+    /// overflows `usize`, and [`Error::Truncated`] if the header is truncated.
+    /// Anything after the data section is left in `reader`.  On
+    /// error, `reader` is left unchanged.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, rpm::Header};
+    /// let mut reader = Reader::new(&[
+    ///     0x8E, 0xAD, 0xE8, 0x01, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 2,
+    ///     0, 0, 0x03, 0xEA, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 2, 0xAB, 0xCD,
+    /// ]);
+    /// let header = Header::parse(&mut reader).unwrap();
+    /// assert_eq!(header.entry_count(), 1);
+    /// assert_eq!(header.data(), &[0xAB, 0xCD]);
+    /// let entry = header.entries().next().unwrap();
+    /// assert_eq!((entry.tag, entry.ty, entry.offset, entry.count), (1002, 7, 0, 2));
+    /// ```
+    pub fn parse(reader: &mut Reader<'a>) -> Result<Self, Error> {
+        reader.read(|reader| {
+            if reader.get_bytes(HEADER_MAGIC.len())? != HEADER_MAGIC {
+                return Err(Error::BadHeaderMagic);
+            }
+            let entry_count = reader.be_u32()? as usize;
+            let data_len = reader.be_u32()? as usize;
+            let index_len = entry_count.checked_mul(16).ok_or(Error::CorruptHeader)?;
+            let index = reader.get_bytes(index_len)?;
+            let data = reader.get_bytes(data_len)?;
+            Ok(Header { index, data })
+        })
+    }
+
+    /// The number of index entries
+    pub fn entry_count(&self) -> usize {
+        self.index.len() / 16
+    }
+
+    /// The raw index entries
+    pub fn index(&self) -> &'a [u8] {
+        self.index
+    }
+
+    /// The raw data section
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// An iterator over the index entries, in the order they appear
+    pub fn entries(&self) -> IndexEntries<'a> {
+        IndexEntries {
+            reader: Reader::new(self.index),
+            header_data: self.data,
+        }
+    }
+
+    /// Finds the first index entry with tag `tag`, such as
+    /// [`RPMSIGTAG_RSAHEADER`] or [`RPMSIGTAG_PGP`].
+    pub fn entry(&self, tag: u32) -> Option<IndexEntry<'a>> {
+        self.entries().find(|entry| entry.tag == tag)
+    }
+}
+
+/// An iterator over the entries of a [`Header`]
+#[derive(Clone, Debug)]
+pub struct IndexEntries<'a> {
+    reader: Reader<'a>,
+    header_data: &'a [u8],
+}
+
+impl<'a> Iterator for IndexEntries<'a> {
+    type Item = IndexEntry<'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        // The index length is a multiple of 16, so this cannot fail part way
+        // through an entry.
+        Some(IndexEntry {
+            tag: self.reader.be_u32().ok()?,
+            ty: self.reader.be_u32().ok()?,
+            offset: self.reader.be_u32().ok()?,
+            count: self.reader.be_u32().ok()?,
+            header_data: self.header_data,
+        })
+    }
+}
+
+/// Parses the data of an [`RPMSIGTAG_RSAHEADER`] or [`RPMSIGTAG_PGP`]
+/// entry, which must be exactly one signature packet.  Returns
+/// [`Error::TrailingJunk`] if anything follows the packet, and
+/// [`Error::IllFormedSignature`] if it is not a signature packet.  As with
+/// [`Signature::parse`], no policy is enforced on the signature.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, rpm::parse_rpm_signature};
+/// let data = [0xC2, 12, 4, 0, 22, 8, 0, 0, 0, 0, 0x61, 0x58, 0, 1, 1];
+/// assert_eq!(parse_rpm_signature(&data[..14]).unwrap().pk_algorithm(), 22);
+/// assert_eq!(parse_rpm_signature(&data).unwrap_err(), Error::TrailingJunk { offset: 14 });
+/// ```
+pub fn parse_rpm_signature<'a>(data: &'a [u8]) -> Result<Signature<'a>, Error> {
+    Signature::parse(&parse_single(&mut Reader::new(data))?)
+}
+
+/// Checks `sig`, the data of an [`RPMSIGTAG_RSAHEADER`] entry, against the
+/// main header `header` and `key`.  `digest` must be a fresh hash context of
+/// the signature's hash algorithm, and `verifier` does the public-key
+/// operation.
+///
+/// `header` must be exactly one header, as parsed by [`Header::parse`];
+/// anything after it is reported as [`Error::TrailingJunk`].  Errors from
+/// [`parse_rpm_signature`] and [`Signature::verify_digest`] are passed
+/// through.
+#[cfg(feature = "verify")]
+pub fn verify_rpm_header<H, V>(
+    header: &[u8],
+    sig: &[u8],
+    key: &PublicKey,
+    digest: H,
+    verifier: &V,
+) -> Result<(), Error>
+where
+    H: Digest,
+    H::Output: AsRef<[u8]>,
+    V: Verifier,
+{
+    let sig = parse_rpm_signature(sig)?;
+    let mut reader = HashingReader::new(Reader::new(header), digest);
+    reader.read(Header::parse)?;
+    let rest = reader.remaining().len();
+    if rest != 0 {
+        return Err(Error::TrailingJunk {
+            offset: header.len() - rest,
+        });
+    }
+    sig.verify_digest(key, reader.into_hasher(), verifier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    static RPM: &[u8] = include_bytes!("../../data/lua-5.4.2-1.fc33.x86_64.rpm");
+
+    #[test]
+    fn parses_signature_header() {
+        let mut reader = Reader::new(&RPM[96..]);
+        let header = Header::parse(&mut reader).unwrap();
+        assert_eq!(header.entry_count(), 9);
+        assert_eq!(header.data().len(), 4244);
+        assert_eq!(reader.offset(), 16 + 9 * 16 + 4244);
+        let entries: Vec<_> = header
+            .entries()
+            .map(|e| (e.tag, e.ty, e.offset, e.count))
+            .collect();
+        assert_eq!(entries.len(), 9);
+        assert_eq!(entries[0], (62, 7, 4228, 16));
+        assert_eq!(entries[5], (RPMSIGTAG_PGP, 7, 676, 566));
+        for entry in header.entries() {
+            entry.data().unwrap();
+        }
+        for &tag in &[RPMSIGTAG_RSAHEADER, RPMSIGTAG_PGP] {
+            let data = header.entry(tag).unwrap().data().unwrap();
+            assert_eq!(data.len(), 566);
+            let packet = super::super::packet::parse_single(&mut Reader::new(data)).unwrap();
+            assert_eq!(packet.tag(), 2);
+        }
+        let sig = parse_rpm_signature(header.entry(RPMSIGTAG_PGP).unwrap().data().unwrap());
+        assert_eq!(sig.unwrap().version(), 4);
+        // the SHA-1 digest is a NUL-terminated hex string
+        assert_eq!(header.entry(269).unwrap().data().unwrap().len(), 41);
+        assert!(header.entry(1).is_none());
+    }
+
+    #[test]
+    fn locates_signed_header() {
+        let mut reader = Reader::new_at(&RPM[96..], 96);
+        Header::parse(&mut reader).unwrap();
+        assert_eq!(reader.offset(), 4500);
+        // padding to a multiple of 8 bytes
+        reader.skip(4).unwrap();
+        let start = reader.offset();
+        let header = Header::parse(&mut reader).unwrap();
+        let signed = &RPM[start..reader.offset()];
+        assert_eq!(signed[..8], HEADER_MAGIC);
+        assert_eq!(
+            signed.len(),
+            16 + header.index().len() + header.data().len()
+        );
+        assert_eq!((start, signed.len()), (4504, 7997));
+    }
+
+    #[test]
+    fn rejects_bad_rpm_signatures() {
+        let header = Header::parse(&mut Reader::new(&RPM[96..])).unwrap();
+        let data = header.entry(RPMSIGTAG_RSAHEADER).unwrap().data().unwrap();
+        assert!(parse_rpm_signature(data).is_ok());
+        let mut junk = data.to_owned();
+        junk.push(0);
+        assert_eq!(
+            parse_rpm_signature(&junk).unwrap_err(),
+            Error::TrailingJunk { offset: 566 }
+        );
+        junk.extend_from_slice(data);
+        assert_eq!(
+            parse_rpm_signature(&junk[..566 * 2]).unwrap_err(),
+            Error::TrailingJunk { offset: 566 }
+        );
+        assert_eq!(
+            parse_rpm_signature(&data[..565]).unwrap_err(),
+            Error::Truncated {
+                needed: 563,
+                available: 562
+            }
+        );
+        assert_eq!(parse_rpm_signature(&[]).unwrap_err(), Error::PrematureEOF);
+        assert_eq!(
+            parse_rpm_signature(&[0xCD, 1, b'a']).unwrap_err(),
+            Error::IllFormedSignature
+        );
+    }
+
+    #[test]
+    fn entry_bounds() {
+        let data = [1u8, 0, 2, 0];
+        let entry = |ty, offset, count| {
+            IndexEntry {
+                tag: 1,
+                ty,
+                offset,
+                count,
+                header_data: &data,
+            }
+            .data()
+        };
+        assert_eq!(entry(7, 0, 4), Ok(&data[..]));
+        assert_eq!(entry(7, 4, 0), Ok(&[][..]));
+        assert_eq!(entry(4, 0, 1), Ok(&data[..]));
+        assert_eq!(entry(3, 2, 1), Ok(&data[2..]));
+        assert_eq!(entry(8, 0, 2), Ok(&data[..]));
+        assert_eq!(entry(6, 1, 1), Ok(&data[1..2]));
+        for &(ty, offset, count) in &[
+            (7, 0, 5),
+            (7, 5, 0),
+            (7, !0, 1),
+            (5, 0, 1),
+            (5, 0, !0),
+            (3, 3, 1),
+            (8, 0, 3),
+            (6, 0, 0),
+            (0, 0, 0),
+            (10, 0, 1),
+        ] {
+            assert_eq!(entry(ty, offset, count), Err(Error::CorruptHeader));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_headers() {
+        let header = &RPM[96..96 + 16 + 9 * 16 + 4244];
+        let mut bad = header.to_owned();
+        bad[7] = 1;
+        let mut huge = HEADER_MAGIC.to_vec();
+        huge.extend_from_slice(&[0xFF; 8]);
+        let mut cases = vec![
+            (&bad[..], Error::BadHeaderMagic),
+            (
+                &huge,
+                if cfg!(target_pointer_width = "32") {
+                    Error::CorruptHeader
+                } else {
+                    Error::Truncated {
+                        needed: 0xFFFF_FFFF * 16,
+                        available: 0,
+                    }
+                },
+            ),
+        ];
+        for &(len, needed, available) in &[
+            (0, 8, 0),
+            (7, 8, 7),
+            (8, 4, 0),
+            (15, 4, 3),
+            (16, 9 * 16, 0),
+            (16 + 9 * 16, 4244, 0),
+            (header.len() - 1, 4244, 4243),
+        ] {
+            cases.push((&header[..len], Error::Truncated { needed, available }));
+        }
+        // errors leave the reader alone
+        for &(data, ref err) in &cases {
+            let mut reader = Reader::new(data);
+            assert_eq!(Header::parse(&mut reader).unwrap_err(), *err);
+            assert_eq!(reader.len(), data.len());
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! A minimal SHA-1 implementation, used only to compute OpenPGP version 4 key
+//! fingerprints.  SHA-1 is broken for collision resistance; do not use this
+//! for anything else.
+
+extern crate core;
+
+/// SHA-1 hash state
+#[derive(Clone)]
+pub(crate) struct Sha1 {
+    state: [u32; 5],
+    block: [u8; 64],
+    block_len: usize,
+    total_len: u64,
+}
+
+impl Sha1 {
+    /// Create a new hash state
+    pub(crate) fn new() -> Self {
+        Sha1 {
+            state: [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0],
+            block: [0; 64],
+            block_len: 0,
+            total_len: 0,
+        }
+    }
+
+    /// Add `data` to the hash
+    pub(crate) fn update(&mut self, mut data: &[u8]) {
+        self.total_len = self.total_len.wrapping_add(data.len() as u64);
+        while !data.is_empty() {
+            let to_copy = core::cmp::min(64 - self.block_len, data.len());
+            self.block[self.block_len..self.block_len + to_copy].copy_from_slice(&data[..to_copy]);
+            self.block_len += to_copy;
+            data = &data[to_copy..];
+            if self.block_len == 64 {
+                let block = self.block;
+                self.compress(&block);
+                self.block_len = 0;
+            }
+        }
+    }
+
+    /// Finish computing the hash
+    pub(crate) fn finalize(mut self) -> [u8; 20] {
+        let bit_len = self.total_len.wrapping_mul(8);
+        self.update(&[0x80]);
+        while self.block_len != 56 {
+            self.update(&[0]);
+        }
+        let mut len_bytes = [0u8; 8];
+        for (i, byte) in len_bytes.iter_mut().enumerate() {
+            *byte = (bit_len >> (56 - 8 * i)) as u8;
+        }
+        self.update(&len_bytes);
+        let mut out = [0u8; 20];
+        for (i, word) in self.state.iter().enumerate() {
+            for j in 0..4 {
+                out[4 * i + j] = (word >> (24 - 8 * j)) as u8;
+            }
+        }
+        out
+    }
+
+    fn compress(&mut self, block: &[u8; 64]) {
+        let mut w = [0u32; 80];
+        for i in 0..16 {
+            w[i] = u32::from(block[4 * i]) << 24
+                | u32::from(block[4 * i + 1]) << 16
+                | u32::from(block[4 * i + 2]) << 8
+                | u32::from(block[4 * i + 3]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = self.state;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0...19 => ((b & c) | (!b & d), 0x5A827999),
+                20...39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40...59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in self.state.iter_mut().zip(&[a, b, c, d, e]) {
+            *state = state.wrapping_add(*value);
+        }
+    }
+}
+
+#[cfg(feature = "digest")]
+impl super::hashing::Digest for Sha1 {
+    type Output = [u8; 20];
+    fn update(&mut self, data: &[u8]) {
+        Sha1::update(self, data)
+    }
+    fn finalize(self) -> [u8; 20] {
+        Sha1::finalize(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    fn sha1(data: &[u8]) -> [u8; 20] {
+        let mut ctx = Sha1::new();
+        ctx.update(data);
+        ctx.finalize()
+    }
+    #[test]
+    fn test_vectors() {
+        assert_eq!(
+            sha1(b""),
+            *b"\xda\x39\xa3\xee\x5e\x6b\x4b\x0d\x32\x55\xbf\xef\x95\x60\x18\x90\xaf\xd8\x07\x09"
+        );
+        assert_eq!(
+            sha1(b"abc"),
+            *b"\xa9\x99\x3e\x36\x47\x06\x81\x6a\xba\x3e\x25\x71\x78\x50\xc2\x6c\x9c\xd0\xd8\x9d"
+        );
+        assert_eq!(
+            sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            *b"\x84\x98\x3e\x44\x1c\x3b\xd2\x6e\xba\xae\x4a\xa1\xf9\x51\x29\xe5\xe5\x46\x70\xf1"
+        );
+        let mut ctx = Sha1::new();
+        for _ in 0..1000 {
+            ctx.update(&[b'a'; 1000][..]);
+        }
+        assert_eq!(
+            ctx.finalize(),
+            *b"\x34\xaa\x97\x3c\xd4\xc4\xda\xa4\xf6\x1e\xeb\x2b\xdb\xad\x27\x31\x65\x34\x01\x6f"
+        );
+    }
+}
@@ -1,8 +1,12 @@
 //! OpenPGP signatures
 
+use super::algorithm::PublicKeyAlgorithm;
 use super::{packet, Error, Reader};
 use packet::get_varlen_bytes;
+use util::ct_eq;
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
 extern crate core;
 
 #[derive(PartialEq, Eq, Copy, Clone, Debug)]
@@ -15,93 +19,220 @@ pub enum AllowWeakHashes {
 }
 
 /// Signature types
-#[derive(PartialEq, Eq, Copy, Clone, Debug)]
-#[repr(u8)]
+///
+/// Two [`SignatureType`]s are equal if they have the same [`Self::to_u8`]
+/// value, so `SignatureType::Unknown(0x00)` is equal to
+/// [`SignatureType::Binary`].  Matching on the variants does not work this
+/// way, so use [`Self::from_u8`] to create values rather than building an
+/// [`SignatureType::Unknown`] directly.
+#[derive(Eq, Copy, Clone, Debug)]
 pub enum SignatureType {
-    /// Signature of a binary document
-    Binary = 0,
-    /// Signature of a text document with CRLF line endings
-    Text = 1,
-    /// Standalone
-    Standalone = 2,
-    /// Generic certification
-    GenericCert = 0x10,
-    /// Persona certification
-    PersonaCert = 0x11,
-    /// Casual certification
-    CasualCert = 0x12,
-    /// Positive certification
-    PositiveCert = 0x13,
-    /// Subkey binding
-    SubkeyBinding = 0x18,
-    /// Primary-key binding
-    PrimaryKeyBinding = 0x19,
-    /// Signature directly on a key
-    KeySig = 0x1F,
-    /// Primary key revocation
-    PrimaryKeyRevocation = 0x20,
-    /// Subkey revocation
-    SubkeyRevocation = 0x28,
-    /// Certification revocation
-    CertificationRevocatin = 0x30,
-    /// Timestamp
-    Timestamp = 0x40,
+    /// Signature of a binary document (0x00)
+    Binary,
+    /// Signature of a text document with CRLF line endings (0x01)
+    Text,
+    /// Standalone (0x02)
+    Standalone,
+    /// Generic certification (0x10)
+    GenericCert,
+    /// Persona certification (0x11)
+    PersonaCert,
+    /// Casual certification (0x12)
+    CasualCert,
+    /// Positive certification (0x13)
+    PositiveCert,
+    /// Subkey binding (0x18)
+    SubkeyBinding,
+    /// Primary-key binding (0x19)
+    PrimaryKeyBinding,
+    /// Signature directly on a key (0x1F)
+    KeySig,
+    /// Primary key revocation (0x20)
+    PrimaryKeyRevocation,
+    /// Subkey revocation (0x28)
+    SubkeyRevocation,
+    /// Certification revocation (0x30)
+    CertificationRevocatin,
+    /// Timestamp (0x40)
+    Timestamp,
+    /// Any other signature type.  [`SignatureType::from_u8`] only uses this
+    /// for values that no other variant stands for.
+    Unknown(u8),
 }
 
-/// Rivest-Shamir-Aldeman (RSA) cryptography
-const OPENPGP_PUBLIC_KEY_RSA: u8 = 1;
-
-/// Legacy encrypt-only RSA
-const OPENPGP_PUBLIC_KEY_LEGACY_RSA_ENCRYPT_ONLY: u8 = 2;
-
-/// Legacy sign-only RSA
-const OPENPGP_PUBLIC_KEY_LEGACY_RSA_SIGN_ONLY: u8 = 3;
-
-/// Encrypt-only ElGamal
-const OPENPGP_PUBLIC_KEY_ELGAMAL_ENCRYPT_ONLY: u8 = 16;
+impl PartialEq for SignatureType {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_u8() == other.to_u8()
+    }
+}
 
-/// Finite-field Digital Signature Algorithm (DSA)
-const OPENPGP_PUBLIC_KEY_DSA: u8 = 17;
+impl core::hash::Hash for SignatureType {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.to_u8().hash(state)
+    }
+}
 
-/// Elliptic-curve Diffe-Hellman
-const OPENPGP_PUBLIC_KEY_ECDH: u8 = 18;
+impl SignatureType {
+    /// Converts a raw signature type to a [`SignatureType`]
+    ///
+    /// ```rust
+    /// # use openpgp_parser::signature::SignatureType;
+    /// assert_eq!(SignatureType::from_u8(0x13), SignatureType::PositiveCert);
+    /// assert_eq!(SignatureType::from_u8(0x50), SignatureType::Unknown(0x50));
+    /// ```
+    pub fn from_u8(ty: u8) -> Self {
+        match ty {
+            0x00 => SignatureType::Binary,
+            0x01 => SignatureType::Text,
+            0x02 => SignatureType::Standalone,
+            0x10 => SignatureType::GenericCert,
+            0x11 => SignatureType::PersonaCert,
+            0x12 => SignatureType::CasualCert,
+            0x13 => SignatureType::PositiveCert,
+            0x18 => SignatureType::SubkeyBinding,
+            0x19 => SignatureType::PrimaryKeyBinding,
+            0x1F => SignatureType::KeySig,
+            0x20 => SignatureType::PrimaryKeyRevocation,
+            0x28 => SignatureType::SubkeyRevocation,
+            0x30 => SignatureType::CertificationRevocatin,
+            0x40 => SignatureType::Timestamp,
+            ty => SignatureType::Unknown(ty),
+        }
+    }
 
-/// Elliptic-curve Digital Signature Algorithm
-const OPENPGP_PUBLIC_KEY_ECDSA: u8 = 19;
+    /// Converts a [`SignatureType`] to a raw signature type
+    ///
+    /// ```rust
+    /// # use openpgp_parser::signature::SignatureType;
+    /// assert_eq!(SignatureType::SubkeyBinding.to_u8(), 0x18);
+    /// assert_eq!(SignatureType::Unknown(0x50).to_u8(), 0x50);
+    /// ```
+    pub fn to_u8(self) -> u8 {
+        match self {
+            SignatureType::Binary => 0x00,
+            SignatureType::Text => 0x01,
+            SignatureType::Standalone => 0x02,
+            SignatureType::GenericCert => 0x10,
+            SignatureType::PersonaCert => 0x11,
+            SignatureType::CasualCert => 0x12,
+            SignatureType::PositiveCert => 0x13,
+            SignatureType::SubkeyBinding => 0x18,
+            SignatureType::PrimaryKeyBinding => 0x19,
+            SignatureType::KeySig => 0x1F,
+            SignatureType::PrimaryKeyRevocation => 0x20,
+            SignatureType::SubkeyRevocation => 0x28,
+            SignatureType::CertificationRevocatin => 0x30,
+            SignatureType::Timestamp => 0x40,
+            SignatureType::Unknown(ty) => ty,
+        }
+    }
 
-/// ElGamal signing and encryption.  This is insecure as ElGamal signatures
-/// have been broken.
-const OPENPGP_PUBLIC_KEY_INSECURE_ELGAMAL_SIGN_ENCRYPT: u8 = 20;
+    /// Returns [`true`] for certifications of a user ID (types 0x10 to 0x13)
+    pub fn is_certification(&self) -> bool {
+        match *self {
+            SignatureType::GenericCert
+            | SignatureType::PersonaCert
+            | SignatureType::CasualCert
+            | SignatureType::PositiveCert => true,
+            SignatureType::Binary
+            | SignatureType::Text
+            | SignatureType::Standalone
+            | SignatureType::SubkeyBinding
+            | SignatureType::PrimaryKeyBinding
+            | SignatureType::KeySig
+            | SignatureType::PrimaryKeyRevocation
+            | SignatureType::SubkeyRevocation
+            | SignatureType::CertificationRevocatin
+            | SignatureType::Timestamp
+            | SignatureType::Unknown(_) => false,
+        }
+    }
 
-/// Finite-field Diffe-Hellman
-const OPENPGP_PUBLIC_KEY_DH: u8 = 21;
+    /// Returns [`true`] for signatures of a binary or text document (types 0
+    /// and 1).  RPM only accepts binary document signatures.
+    pub fn is_document(&self) -> bool {
+        match *self {
+            SignatureType::Binary | SignatureType::Text => true,
+            SignatureType::Standalone
+            | SignatureType::GenericCert
+            | SignatureType::PersonaCert
+            | SignatureType::CasualCert
+            | SignatureType::PositiveCert
+            | SignatureType::SubkeyBinding
+            | SignatureType::PrimaryKeyBinding
+            | SignatureType::KeySig
+            | SignatureType::PrimaryKeyRevocation
+            | SignatureType::SubkeyRevocation
+            | SignatureType::CertificationRevocatin
+            | SignatureType::Timestamp
+            | SignatureType::Unknown(_) => false,
+        }
+    }
 
-/// Edwards-curve Digital Signature Algorithm
-const OPENPGP_PUBLIC_KEY_EDDSA: u8 = 22;
+    /// Returns [`true`] for revocations of a primary key, subkey, or
+    /// certification (types 0x20, 0x28, and 0x30)
+    pub fn is_revocation(&self) -> bool {
+        match *self {
+            SignatureType::PrimaryKeyRevocation
+            | SignatureType::SubkeyRevocation
+            | SignatureType::CertificationRevocatin => true,
+            SignatureType::Binary
+            | SignatureType::Text
+            | SignatureType::Standalone
+            | SignatureType::GenericCert
+            | SignatureType::PersonaCert
+            | SignatureType::CasualCert
+            | SignatureType::PositiveCert
+            | SignatureType::SubkeyBinding
+            | SignatureType::PrimaryKeyBinding
+            | SignatureType::KeySig
+            | SignatureType::Timestamp
+            | SignatureType::Unknown(_) => false,
+        }
+    }
+}
 
 /// Read a multiprecision integer (MPI) from `reader`.  Value is returned as a
 /// slice.
 pub fn read_mpi<'a>(reader: &mut Reader<'a>) -> Result<&'a [u8], Error> {
     reader.read(|reader| {
-        let bits = 7 + reader.be_u16()? as usize;
-        if bits == 7 {
-            // Empty MPI is invalid
-            return Err(Error::BadMPI);
-        }
-        let mpi_buf = reader.get_bytes(bits >> 3)?;
-        // don’t use ‘Reader::byte’, which mutates the reader
-        if let Some(first_byte) = mpi_buf.get(0) {
-            // check that there are no spurious leading zeros
-            // this is not valid for encrypted MPIs, but we don’t deal with
-            // them, as we only parse signatures
-            if first_byte.leading_zeros() as usize + (bits & 7) != 7 {
-                return Err(Error::BadMPI);
-            }
-        }
+        let bits = reader.be_u16()?;
+        let mpi_buf = reader.get_bytes((usize::from(bits) + 7) >> 3)?;
+        validate_mpi(mpi_buf, bits)?;
         Ok(mpi_buf)
     })
 }
 
+/// Checks that `bytes` are a well-formed MPI with a bit count of
+/// `declared_bits`: there must be exactly `(declared_bits + 7) / 8` bytes, and
+/// the highest set bit of the first byte must be the one implied by
+/// `declared_bits`.  Otherwise, returns [`Error::BadMPI`].  Empty MPIs are
+/// also rejected.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, signature::validate_mpi};
+/// assert_eq!(validate_mpi(&[1, 0xFF], 9), Ok(()));
+/// assert_eq!(validate_mpi(&[1, 0xFF], 10), Err(Error::BadMPI));
+/// assert_eq!(validate_mpi(&[0, 0xFF], 8), Err(Error::BadMPI));
+/// assert_eq!(validate_mpi(&[], 0), Err(Error::BadMPI));
+/// ```
+pub fn validate_mpi(bytes: &[u8], declared_bits: u16) -> Result<(), Error> {
+    let bits = 7 + usize::from(declared_bits);
+    match bytes.first() {
+        // check that there are no spurious leading zeros
+        // this is not valid for encrypted MPIs, but we don’t deal with
+        // them, as we only parse signatures
+        Some(first_byte)
+            if bytes.len() == bits >> 3
+                && first_byte.leading_zeros() as usize + (bits & 7) == 7 =>
+        {
+            Ok(())
+        }
+        // empty MPI is invalid
+        _ => Err(Error::BadMPI),
+    }
+}
+
 const OPENPGP_HASH_INSECURE_MD5: i32 = 1;
 const OPENPGP_HASH_INSECURE_SHA1: i32 = 2;
 const OPENPGP_HASH_INSECURE_RIPEMD160: i32 = 3;
@@ -141,6 +272,32 @@ const SUBPACKET_SIGNATURE_TARGET: u8 = 31;
 const SUBPACKET_EMBEDDED_SIGNATURE: u8 = 32;
 const SUBPACKET_FINGERPRINT: u8 = 33;
 
+/// Key flag: the key may certify other keys
+pub const KEY_FLAG_CERTIFY: u8 = 0x01;
+/// Key flag: the key may sign data
+pub const KEY_FLAG_SIGN: u8 = 0x02;
+/// Key flag: the key may encrypt communications
+pub const KEY_FLAG_ENCRYPT_COMMUNICATIONS: u8 = 0x04;
+/// Key flag: the key may encrypt storage
+pub const KEY_FLAG_ENCRYPT_STORAGE: u8 = 0x08;
+/// Key flag: the private key may have been split by a secret-sharing mechanism
+pub const KEY_FLAG_SPLIT: u8 = 0x10;
+/// Key flag: the key may be used for authentication
+pub const KEY_FLAG_AUTHENTICATE: u8 = 0x20;
+/// Key flag: the private key may be in the possession of more than one person
+pub const KEY_FLAG_GROUP: u8 = 0x80;
+
+/// Revocation reason: no reason specified
+pub const REVOCATION_REASON_UNSPECIFIED: u8 = 0;
+/// Revocation reason: the key is superseded by another key
+pub const REVOCATION_REASON_SUPERSEDED: u8 = 1;
+/// Revocation reason: the key material has been compromised
+pub const REVOCATION_REASON_COMPROMISED: u8 = 2;
+/// Revocation reason: the key is retired and no longer used
+pub const REVOCATION_REASON_RETIRED: u8 = 3;
+/// Revocation reason: the user ID is no longer valid
+pub const REVOCATION_REASON_USER_ID_INVALID: u8 = 32;
+
 /// Return the number of MPIs for the public-key algorithm `alg`, checking it
 /// against signature version `sig_version`.  Returns `Err` if the algorithm is
 /// invalid or unsupported for the given signature version.
@@ -148,22 +305,25 @@ pub fn pkey_alg_mpis(alg: u8, sig_version: u8) -> Result<u8, Error> {
     let is_v4 = match sig_version {
         3 => false,
         4 => true,
-        _ => return Err(Error::UnsupportedSignatureVersion),
+        _ => return Err(Error::UnsupportedSignatureVersion(sig_version)),
     };
-    match alg {
-        OPENPGP_PUBLIC_KEY_LEGACY_RSA_ENCRYPT_ONLY
-        | OPENPGP_PUBLIC_KEY_ELGAMAL_ENCRYPT_ONLY
-        | OPENPGP_PUBLIC_KEY_INSECURE_ELGAMAL_SIGN_ENCRYPT
-        | OPENPGP_PUBLIC_KEY_ECDH
-        | OPENPGP_PUBLIC_KEY_DH => Err(Error::InvalidPkeyAlgorithm(alg)),
-        OPENPGP_PUBLIC_KEY_RSA | OPENPGP_PUBLIC_KEY_LEGACY_RSA_SIGN_ONLY => Ok(1),
-        OPENPGP_PUBLIC_KEY_EDDSA if is_v4 => Ok(2),
-        OPENPGP_PUBLIC_KEY_DSA => Ok(2),
-        OPENPGP_PUBLIC_KEY_ECDSA if is_v4 => Err(Error::UnsupportedPkeyAlgorithm(alg)),
-        OPENPGP_PUBLIC_KEY_ECDSA | OPENPGP_PUBLIC_KEY_EDDSA => {
+    let ty = PublicKeyAlgorithm::from_u8(alg);
+    if !ty.can_sign() {
+        return Err(match ty {
+            PublicKeyAlgorithm::Unknown(_) => Error::UnknownPkeyAlgorithm(alg),
+            _ => Error::InvalidPkeyAlgorithm(alg),
+        });
+    }
+    match ty {
+        PublicKeyAlgorithm::Rsa | PublicKeyAlgorithm::RsaSign => Ok(1),
+        PublicKeyAlgorithm::EdDsa if is_v4 => Ok(2),
+        PublicKeyAlgorithm::Dsa => Ok(2),
+        PublicKeyAlgorithm::Ecdsa if is_v4 => Err(Error::UnsupportedPkeyAlgorithm(alg)),
+        PublicKeyAlgorithm::Ecdsa | PublicKeyAlgorithm::EdDsa => {
             Err(Error::PkeyAlgorithmRequiresV4Sig(alg))
         }
-        _ => Err(Error::UnknownPkeyAlgorithm(alg)),
+        // can_sign() is false for everything else
+        _ => Err(Error::InvalidPkeyAlgorithm(alg)),
     }
 }
 
@@ -318,7 +478,7 @@ pub fn parse<'a>(
     allow_weak_hashes: AllowWeakHashes,
     expected_type: SignatureType,
 ) -> Result<SigInfo, Error> {
-    Reader::read_all(data, Error::TrailingJunk, |reader| {
+    Reader::read_all_strict(data, |reader| {
         read_signature(reader, timestamp, allow_weak_hashes, expected_type)
     })
 }
@@ -334,7 +494,7 @@ pub fn read_signature<'a>(
     if packet.tag() != 2 {
         return Err(Error::IllFormedSignature);
     }
-    Reader::read_all(packet.contents(), Error::TrailingJunk, |e| {
+    Reader::read_all_strict(packet.contents(), |e| {
         parse_packet_body(e, timestamp, allow_weak_hashes, expected_type)
     })
 }
@@ -359,7 +519,7 @@ fn parse_packet_body<'a>(
     };
     let check_sig_type = |reader: &mut Reader| {
         let actual_type = reader.byte()?;
-        if actual_type == expected_type as u8 {
+        if actual_type == expected_type.to_u8() {
             Ok(())
         } else {
             Err(Error::WrongSignatureType {
@@ -387,22 +547,14 @@ fn parse_packet_body<'a>(
             pkey_alg = reader.byte()?;
             hash_alg = reader.byte()?;
             let hashed_subpackets = reader.be_u16()?;
-            Reader::read_all(
-                reader.get_bytes(hashed_subpackets as _)?,
-                Error::TrailingJunk,
-                |reader| {
-                    Ok(while !reader.is_empty() {
-                        Reader::read_all(
-                            get_varlen_bytes(reader)?,
-                            Error::TrailingJunk,
-                            |reader| {
-                                let tag_byte = reader.byte()?;
-                                process_subpacket(reader, timestamp, tag_byte & 0x7F, &mut siginfo)
-                            },
-                        )?
-                    })
-                },
-            )?;
+            Reader::read_all_strict(reader.get_bytes(hashed_subpackets as _)?, |reader| {
+                Ok(while !reader.is_empty() {
+                    Reader::read_all_strict(get_varlen_bytes(reader)?, |reader| {
+                        let tag_byte = reader.byte()?;
+                        process_subpacket(reader, timestamp, tag_byte & 0x7F, &mut siginfo)
+                    })?
+                })
+            })?;
             // The only non-hashed subpacket allowed is the key ID, and only if
             // it has not already been seen.
             key_id = match siginfo.id {
@@ -424,12 +576,12 @@ fn parse_packet_body<'a>(
                 }
             };
             if let Some(s) = siginfo.fpr {
-                if s[12..] != key_id[..] {
+                if !ct_eq(&s[12..], &key_id) {
                     return Err(Error::IllFormedSignature);
                 }
             }
         }
-        _ => return Err(Error::UnsupportedSignatureVersion),
+        _ => return Err(Error::UnsupportedSignatureVersion(version)),
     }
     let mpis = pkey_alg_mpis(pkey_alg, version)?;
     if i32::from(hash_alg) == OPENPGP_HASH_INSECURE_MD5 {
@@ -457,10 +609,762 @@ fn parse_packet_body<'a>(
     })
 }
 
+/// A signature packet, broken up into its component fields.
+///
+/// Unlike [`read_signature`], [`Signature::parse`] only checks that the
+/// packet is syntactically valid.  It does not enforce any policy on the
+/// algorithms, subpackets, or creation time of the signature.
+#[derive(Clone, Debug)]
+pub struct Signature<'a> {
+    version: u8,
+    sig_type: u8,
+    pkey_alg: u8,
+    hash_alg: u8,
+    /// The creation time and key ID of a version 3 signature
+    v3_fields: Option<(u32, [u8; 8])>,
+    hashed_subpackets: &'a [u8],
+    unhashed_subpackets: &'a [u8],
+    hashed_material: &'a [u8],
+    hash_prefix: [u8; 2],
+    mpis: &'a [u8],
+}
+
+/// An iterator over the subpackets in a subpacket area.  Each item is a tuple
+/// of the critical bit, the subpacket type (with the critical bit masked off),
+/// and the subpacket body.
+///
+/// If a subpacket is malformed, an error is yielded once, after which the
+/// iterator always returns [`None`].
+///
+/// ```rust
+/// # use openpgp_parser::{Error, signature::SubpacketIter};
+/// let mut iter = SubpacketIter::new(&[5, 2, 0x60, 0xF, 0x77, 0x1A, 2, 0x90, 1, 0]);
+/// assert_eq!(iter.next(), Some(Ok((false, 2, &[0x60, 0xF, 0x77, 0x1A][..]))));
+/// assert_eq!(iter.next(), Some(Ok((true, 0x10, &[1][..]))));
+/// assert_eq!(iter.next(), Some(Err(Error::IllFormedSignature)));
+/// assert_eq!(iter.next(), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SubpacketIter<'a> {
+    reader: Reader<'a>,
+}
+
+impl<'a> SubpacketIter<'a> {
+    /// Creates an iterator over the subpackets in `area`
+    pub fn new(area: &'a [u8]) -> Self {
+        Self {
+            reader: Reader::new(area),
+        }
+    }
+}
+
+impl<'a> Iterator for SubpacketIter<'a> {
+    type Item = Result<(bool, u8, &'a [u8]), Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reader.is_empty() {
+            return None;
+        }
+        let subpacket = get_varlen_bytes(&mut self.reader).and_then(|subpacket| {
+            match subpacket.split_first() {
+                Some((&tag, body)) => Ok((tag & 0x80 != 0, tag & 0x7F, body)),
+                None => Err(Error::IllFormedSignature),
+            }
+        });
+        if subpacket.is_err() {
+            self.reader = Reader::empty();
+        }
+        Some(subpacket)
+    }
+}
+
+impl<'a> core::iter::FusedIterator for SubpacketIter<'a> {}
+
+/// An iterator over the notation data subpackets in the hashed area of a
+/// [`Signature`].  Each item is a tuple of the human-readable flag, the name,
+/// and the value.
+///
+/// If a notation is malformed, [`Error::IllFormedSignature`] is yielded once,
+/// after which the iterator always returns [`None`].
+#[derive(Clone, Debug)]
+pub struct Notations<'a> {
+    subpackets: SubpacketIter<'a>,
+}
+
+impl<'a> Iterator for Notations<'a> {
+    type Item = Result<(bool, &'a [u8], &'a [u8]), Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let body = loop {
+            match self.subpackets.next()? {
+                Ok((_, SUBPACKET_NOTATION, body)) => break body,
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        };
+        let notation = Reader::read_all(body, Error::IllFormedSignature, |reader| {
+            let flags = reader.get_bytes(4)?;
+            let name_len = reader.be_u16()?;
+            let value_len = reader.be_u16()?;
+            let name = reader.get_bytes(name_len.into())?;
+            let value = reader.get_bytes(value_len.into())?;
+            Ok((flags[0] & 0x80 != 0, name, value))
+        })
+        .map_err(|e| match e {
+            Error::PrematureEOF | Error::Truncated { .. } => Error::IllFormedSignature,
+            e => e,
+        });
+        if notation.is_err() {
+            self.subpackets = SubpacketIter::new(&[]);
+        }
+        Some(notation)
+    }
+}
+
+impl<'a> core::iter::FusedIterator for Notations<'a> {}
+
+/// Reads a subpacket area, checking that every subpacket in it is properly
+/// framed.
+fn read_subpacket_area<'a>(reader: &mut Reader<'a>) -> Result<&'a [u8], Error> {
+    let len = reader.be_u16()?;
+    let area = reader.get_bytes(len as _)?;
+    for subpacket in SubpacketIter::new(area) {
+        subpacket?;
+    }
+    Ok(area)
+}
+
+impl<'a> Signature<'a> {
+    /// Parses a signature packet.  Versions 3 and 4 are supported; other
+    /// versions, including the version 5 and 6 signatures of newer OpenPGP
+    /// standards, result in [`Error::UnsupportedSignatureVersion`] with the
+    /// version.
+    pub fn parse(packet: &packet::Packet<'a>) -> Result<Self, Error> {
+        if packet.tag() != 2 {
+            return Err(Error::IllFormedSignature);
+        }
+        let contents = packet.contents();
+        Reader::read_all_strict(contents, |reader| {
+            let version = reader.byte()?;
+            let (sig_type, pkey_alg, hash_alg, v3_fields, hashed_subpackets, unhashed_subpackets);
+            let hashed_material;
+            match version {
+                3 => {
+                    if reader.byte()? != 5 {
+                        return Err(Error::IllFormedSignature);
+                    }
+                    sig_type = reader.byte()?;
+                    let creation_time = reader.be_u32()?;
+                    let mut key_id = [0u8; 8];
+                    key_id.copy_from_slice(reader.get_bytes(8)?);
+                    v3_fields = Some((creation_time, key_id));
+                    pkey_alg = reader.byte()?;
+                    hash_alg = reader.byte()?;
+                    hashed_subpackets = &[][..];
+                    unhashed_subpackets = &[][..];
+                    // the signature type and creation time
+                    hashed_material = &contents[2..7];
+                }
+                4 => {
+                    sig_type = reader.byte()?;
+                    pkey_alg = reader.byte()?;
+                    hash_alg = reader.byte()?;
+                    v3_fields = None;
+                    hashed_subpackets = read_subpacket_area(reader)?;
+                    hashed_material = &contents[..reader.offset()];
+                    unhashed_subpackets = read_subpacket_area(reader)?;
+                }
+                _ => return Err(Error::UnsupportedSignatureVersion(version)),
+            }
+            let mut hash_prefix = [0u8; 2];
+            hash_prefix.copy_from_slice(reader.get_bytes(2)?);
+            let len = reader.len();
+            let mpis = reader.get_bytes(len).expect("length correct");
+            if mpis.is_empty() {
+                return Err(Error::Truncated {
+                    needed: 1,
+                    available: 0,
+                });
+            }
+            Ok(Signature {
+                version,
+                sig_type,
+                pkey_alg,
+                hash_alg,
+                v3_fields,
+                hashed_subpackets,
+                unhashed_subpackets,
+                hashed_material,
+                hash_prefix,
+                mpis,
+            })
+        })
+    }
+
+    /// The signature version (3 or 4)
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The raw signature type
+    pub fn signature_type(&self) -> u8 {
+        self.sig_type
+    }
+
+    /// The raw public-key algorithm
+    pub fn pk_algorithm(&self) -> u8 {
+        self.pkey_alg
+    }
+
+    /// The raw hash algorithm
+    pub fn hash_algorithm(&self) -> u8 {
+        self.hash_alg
+    }
+
+    /// The creation time of the signature, in seconds since the Unix epoch.
+    /// For version 4 signatures, this is taken from the first creation time
+    /// subpacket in the hashed subpacket area; creation times in the unhashed
+    /// area are not protected by the signature and are ignored.  Returns
+    /// [`None`] if there is no well-formed creation time subpacket.
+    ///
+    /// OpenPGP timestamps are unsigned, so they do not overflow in 2038.
+    /// Callers should keep them as `u32` (or widen them to `u64` or `i64`)
+    /// rather than converting them to `i32`.
+    pub fn creation_time(&self) -> Option<u32> {
+        if let Some((creation_time, _)) = self.v3_fields {
+            return Some(creation_time);
+        }
+        self.hashed_timestamp(SUBPACKET_CREATION_TIME)
+    }
+
+    /// The signature expiration time from the hashed subpacket area, in
+    /// seconds after [`Self::creation_time`].  A value of 0 means that the
+    /// signature does not expire.  Returns [`None`] if there is no
+    /// well-formed signature expiration time subpacket, which also means that
+    /// the signature does not expire.
+    pub fn expiration(&self) -> Option<u32> {
+        self.hashed_timestamp(SUBPACKET_SIG_EXPIRATION_TIME)
+    }
+
+    /// Checks whether the signature has expired at time `now`.  A signature
+    /// expires once `now` reaches its creation time plus
+    /// [`Self::expiration`]; an expiration past the end of the 32-bit
+    /// timestamp range is never reached.  A signature that sets an expiration
+    /// time but has no creation time is treated as expired, since it is not
+    /// possible to tell when it expires.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next, signature::Signature};
+    /// let data = [
+    ///     0xC2, 25, 4, 0, 22, 8, 0, 12, 5, 2, 0, 0, 0, 100, 5, 3, 0, 0, 0, 10,
+    ///     0, 0, 0x61, 0x58, 0, 1, 1,
+    /// ];
+    /// let packet = next(&mut Reader::new(&data)).unwrap().unwrap();
+    /// let sig = Signature::parse(&packet).unwrap();
+    /// assert_eq!(sig.expiration(), Some(10));
+    /// assert!(!sig.is_expired(109));
+    /// assert!(sig.is_expired(110));
+    /// ```
+    pub fn is_expired(&self, now: u32) -> bool {
+        match self.expiration() {
+            None | Some(0) => false,
+            Some(expiration) => match self.creation_time() {
+                Some(creation_time) => match creation_time.checked_add(expiration) {
+                    Some(expires_at) => now >= expires_at,
+                    None => false,
+                },
+                None => true,
+            },
+        }
+    }
+
+    /// The key expiration time from the hashed subpacket area, in seconds
+    /// after the creation time of the key.  This is only meaningful in a
+    /// self-signature.  A value of 0 means that the key does not expire.
+    /// Returns [`None`] if there is no well-formed key expiration time
+    /// subpacket, which also means that the key does not expire.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next, signature::Signature};
+    /// let data = [
+    ///     0xC2, 19, 4, 0x13, 22, 8, 0, 6, 5, 9, 0, 1, 0x51, 0x80, 0, 0, 0x61, 0x58, 0, 1, 1,
+    /// ];
+    /// let packet = next(&mut Reader::new(&data)).unwrap().unwrap();
+    /// let sig = Signature::parse(&packet).unwrap();
+    /// assert_eq!(sig.key_expiration(), Some(86400));
+    /// ```
+    pub fn key_expiration(&self) -> Option<u32> {
+        self.hashed_timestamp(SUBPACKET_KEY_EXPIRATION_TIME)
+    }
+
+    /// The first byte of the key flags subpacket in the hashed area, made up
+    /// of `KEY_FLAG_*` bits such as [`KEY_FLAG_SIGN`].  This is only
+    /// meaningful in a self-signature or subkey binding signature, where it
+    /// says what the key may be used for.  Returns [`None`] if there is no
+    /// hashed key flags subpacket, or if it is empty.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next, signature::*};
+    /// let data = [
+    ///     0xC2, 16, 4, 0x18, 22, 8, 0, 3, 2, 27, 0x0C, 0, 0, 0x61, 0x58, 0, 1, 1,
+    /// ];
+    /// let packet = next(&mut Reader::new(&data)).unwrap().unwrap();
+    /// let sig = Signature::parse(&packet).unwrap();
+    /// assert_eq!(sig.key_flags(), Some(KEY_FLAG_ENCRYPT_COMMUNICATIONS | KEY_FLAG_ENCRYPT_STORAGE));
+    /// assert!(!sig.can_sign());
+    /// assert!(!sig.can_certify());
+    /// ```
+    pub fn key_flags(&self) -> Option<u8> {
+        self.hashed_subpacket(SUBPACKET_KEY_FLAGS)
+            .and_then(|body| body.first().cloned())
+    }
+
+    /// Checks if [`Self::key_flags`] allow the key to sign data.  If there are
+    /// no key flags, this returns `false`.
+    pub fn can_sign(&self) -> bool {
+        self.key_flags().unwrap_or(0) & KEY_FLAG_SIGN != 0
+    }
+
+    /// Checks if [`Self::key_flags`] allow the key to certify other keys.  If
+    /// there are no key flags, this returns `false`.
+    pub fn can_certify(&self) -> bool {
+        self.key_flags().unwrap_or(0) & KEY_FLAG_CERTIFY != 0
+    }
+
+    /// The preferred hash algorithms from the hashed subpacket area, most
+    /// preferred first.  This is only meaningful in a self-signature.  The
+    /// algorithms are not checked, and may include insecure or unknown ones.
+    /// Returns [`None`] if there is no hashed preferred hash algorithms
+    /// subpacket.
+    pub fn preferred_hash_algorithms(&self) -> Option<&'a [u8]> {
+        self.hashed_subpacket(SUBPACKET_PREFERRED_HASH)
+    }
+
+    /// The reason for revocation subpacket in the hashed area: a
+    /// `REVOCATION_REASON_*` code such as [`REVOCATION_REASON_COMPROMISED`],
+    /// and a human-readable explanation.  The explanation should be UTF-8,
+    /// but is not checked, and is often empty.  This is only meaningful in a
+    /// revocation signature.  Returns [`None`] if there is no hashed reason
+    /// for revocation subpacket, or if it is empty.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next, signature::*};
+    /// let data = [
+    ///     0xC2, 19, 4, 0x20, 22, 8, 0, 6, 5, 29, 1, b'n', b'e', b'w', 0, 0, 0x61, 0x58, 0, 1, 1,
+    /// ];
+    /// let packet = next(&mut Reader::new(&data)).unwrap().unwrap();
+    /// let sig = Signature::parse(&packet).unwrap();
+    /// assert_eq!(sig.revocation_reason(), Some((REVOCATION_REASON_SUPERSEDED, &b"new"[..])));
+    /// ```
+    pub fn revocation_reason(&self) -> Option<(u8, &'a [u8])> {
+        self.hashed_subpacket(SUBPACKET_REVOCATION_REASON)
+            .and_then(|body| body.split_first())
+            .map(|(&code, explanation)| (code, explanation))
+    }
+
+    /// The signer's user ID subpacket in the hashed area, which names the
+    /// user ID of the key that made the signature.  This is only suitable for
+    /// display: it is chosen by the signer, and need not be a user ID of the
+    /// key.  Returns [`None`] if there is no hashed signer's user ID
+    /// subpacket, or if it is not valid UTF-8; use
+    /// [`Self::signer_user_id_bytes`] to get the raw bytes.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next, signature::Signature};
+    /// let data = [
+    ///     0xC2, 17, 4, 0, 22, 8, 0, 4, 3, 28, b'm', b'e', 0, 0, 0x61, 0x58, 0, 1, 1,
+    /// ];
+    /// let packet = next(&mut Reader::new(&data)).unwrap().unwrap();
+    /// let sig = Signature::parse(&packet).unwrap();
+    /// assert_eq!(sig.signer_user_id(), Some("me"));
+    /// ```
+    pub fn signer_user_id(&self) -> Option<&'a str> {
+        core::str::from_utf8(self.signer_user_id_bytes()?).ok()
+    }
+
+    /// Same as [`Self::signer_user_id`], but returns the raw bytes of the
+    /// subpacket, whether or not they are valid UTF-8
+    pub fn signer_user_id_bytes(&self) -> Option<&'a [u8]> {
+        self.hashed_subpacket(SUBPACKET_SIGNER_USER_ID)
+    }
+
+    /// Checks if this is a revocation signature.  See
+    /// [`SignatureType::is_revocation`].
+    pub fn is_revocation(&self) -> bool {
+        SignatureType::from_u8(self.sig_type).is_revocation()
+    }
+
+    /// Checks if this is a hard revocation of a primary key or subkey.  A hard
+    /// revocation means that the key may have been compromised, so every
+    /// signature made by it must be rejected, even one that predates the
+    /// revocation.  A soft revocation only means that the key is no longer
+    /// used.
+    ///
+    /// A key revocation is soft if its [`Self::revocation_reason`] is
+    /// [`REVOCATION_REASON_SUPERSEDED`] or [`REVOCATION_REASON_RETIRED`], and
+    /// hard otherwise, including when there is no reason at all.
+    /// Certification revocations only revoke a user ID, so they are never hard
+    /// revocations.
+    pub fn is_hard_revocation(&self) -> bool {
+        match SignatureType::from_u8(self.sig_type) {
+            SignatureType::PrimaryKeyRevocation | SignatureType::SubkeyRevocation => {}
+            _ => return false,
+        }
+        let reason = self
+            .revocation_reason()
+            .map_or(REVOCATION_REASON_UNSPECIFIED, |(code, _)| code);
+        reason != REVOCATION_REASON_SUPERSEDED && reason != REVOCATION_REASON_RETIRED
+    }
+
+    /// Reads the body of the first hashed subpacket of type `tag` as a
+    /// big-endian 32-bit integer
+    fn hashed_timestamp(&self, tag: u8) -> Option<u32> {
+        self.hashed_subpacket(tag)
+            .and_then(|body| Reader::read_all(body, (), |r| r.be_u32().map_err(drop)).ok())
+    }
+
+    /// Finds the body of the first hashed subpacket of type `tag`.  The
+    /// subpacket area was checked to be well-formed when the signature was
+    /// parsed.
+    fn hashed_subpacket(&self, tag: u8) -> Option<&'a [u8]> {
+        SubpacketIter::new(self.hashed_subpackets)
+            .filter_map(Result::ok)
+            .find(|&(_, ty, _)| ty == tag)
+            .map(|(_, _, body)| body)
+    }
+
+    /// The notation data subpackets of the signature.  Only the hashed area is
+    /// searched, as notations in the unhashed area are not protected by the
+    /// signature.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next, signature::Signature};
+    /// let data = [
+    ///     0xC2, 30, 4, 0, 22, 8, 0, 17, 16, 20, 0x80, 0, 0, 0, 0, 3, 0, 4,
+    ///     b'a', b'@', b'b', b't', b'e', b's', b't', 0, 0, 0x61, 0x58, 0, 1, 1,
+    /// ];
+    /// let packet = next(&mut Reader::new(&data)).unwrap().unwrap();
+    /// let sig = Signature::parse(&packet).unwrap();
+    /// let mut notations = sig.notations();
+    /// assert_eq!(notations.next(), Some(Ok((true, &b"a@b"[..], &b"test"[..]))));
+    /// assert_eq!(notations.next(), None);
+    /// ```
+    pub fn notations(&self) -> Notations<'a> {
+        Notations {
+            subpackets: SubpacketIter::new(self.hashed_subpackets),
+        }
+    }
+
+    /// Finds the body of the first subpacket of type `tag` for which `pred`
+    /// returns [`true`], looking in the hashed area first and then in the
+    /// unhashed area.
+    fn find_subpacket<F: Fn(&[u8]) -> bool>(&self, tag: u8, pred: F) -> Option<&'a [u8]> {
+        SubpacketIter::new(self.hashed_subpackets)
+            .chain(SubpacketIter::new(self.unhashed_subpackets))
+            .filter_map(Result::ok)
+            .find(|&(_, ty, body)| ty == tag && pred(body))
+            .map(|(_, _, body)| body)
+    }
+
+    /// The fingerprint of the key that made the signature, taken from the
+    /// first version 4 issuer fingerprint subpacket in either subpacket area.
+    /// Returns [`None`] if there is no such subpacket, which is always the case
+    /// for version 3 signatures.
+    ///
+    /// Unhashed subpackets are not protected by the signature, so this is only
+    /// a hint for finding the right key.
+    pub fn issuer_fingerprint(&self) -> Option<[u8; 20]> {
+        self.find_subpacket(SUBPACKET_FINGERPRINT, |body| {
+            body.len() == 21 && body[0] == 4
+        })
+        .map(|body| {
+            let mut fingerprint = [0u8; 20];
+            fingerprint.copy_from_slice(&body[1..]);
+            fingerprint
+        })
+    }
+
+    /// The key ID of the key that made the signature.  For version 3
+    /// signatures, this is part of the signature itself.  For version 4
+    /// signatures, it is taken from the first issuer key ID subpacket in either
+    /// subpacket area, or, if there is none, from the last 8 bytes of
+    /// [`Self::issuer_fingerprint`].
+    ///
+    /// As with [`Self::issuer_fingerprint`], this is only a hint.
+    pub fn issuer_key_id(&self) -> Option<[u8; 8]> {
+        if let Some((_, key_id)) = self.v3_fields {
+            return Some(key_id);
+        }
+        let mut key_id = [0u8; 8];
+        match self.find_subpacket(SUBPACKET_ISSUER_KEYID, |body| body.len() == 8) {
+            Some(body) => key_id.copy_from_slice(body),
+            None => key_id.copy_from_slice(&self.issuer_fingerprint()?[12..]),
+        }
+        Some(key_id)
+    }
+
+    /// The hashed subpacket area.  This is empty for version 3 signatures.
+    pub fn hashed_subpackets(&self) -> &'a [u8] {
+        self.hashed_subpackets
+    }
+
+    /// The unhashed subpacket area.  This is empty for version 3 signatures.
+    pub fn unhashed_subpackets(&self) -> &'a [u8] {
+        self.unhashed_subpackets
+    }
+
+    /// The part of the signature packet that is hashed, after the signed data
+    /// and before [`Self::trailer`].
+    ///
+    /// For a version 4 signature, this is the version, signature type,
+    /// public-key algorithm, hash algorithm, and hashed subpacket area
+    /// (including its length).  For a version 3 signature, it is the signature
+    /// type and creation time.
+    ///
+    /// The hash that is signed is computed over, in order:
+    ///
+    /// 1. The signed data.  For signatures over keys and user IDs, this is
+    ///    the key and user ID packets in the form RFC 4880 section 5.2.4
+    ///    specifies.
+    /// 2. [`Self::hashed_material`].
+    /// 3. [`Self::trailer`], for version 4 signatures only.
+    pub fn hashed_material(&self) -> &'a [u8] {
+        self.hashed_material
+    }
+
+    /// The final trailer hashed for a version 4 signature: the bytes `0x04`
+    /// and `0xFF`, followed by the length of [`Self::hashed_material`] as a
+    /// big-endian 32-bit number.  Version 3 signatures have no trailer, so
+    /// this returns [`None`] for them.
+    pub fn trailer(&self) -> Option<[u8; 6]> {
+        if self.version != 4 {
+            return None;
+        }
+        // The hashed subpacket area is at most 0xFFFF bytes, so this fits
+        let len = self.hashed_material.len() as u32;
+        Some([
+            4,
+            0xFF,
+            (len >> 24) as u8,
+            (len >> 16) as u8,
+            (len >> 8) as u8,
+            len as u8,
+        ])
+    }
+
+    /// The left 16 bits of the signed hash value
+    pub fn hash_prefix(&self) -> [u8; 2] {
+        self.hash_prefix
+    }
+
+    /// Checks that the first two bytes of `computed_digest` match
+    /// [`Self::hash_prefix`].  A digest shorter than two bytes never matches.
+    ///
+    /// This is **not** a security check: anyone can create a signature packet
+    /// with any prefix, and a matching prefix says nothing about whether the
+    /// signature is valid.  It only allows rejecting signatures made over a
+    /// different hash (for instance, with the wrong algorithm) before doing any
+    /// expensive public-key operations.
+    pub fn quick_check(&self, computed_digest: &[u8]) -> bool {
+        match computed_digest.get(..2) {
+            Some(prefix) => ct_eq(prefix, &self.hash_prefix),
+            None => false,
+        }
+    }
+
+    /// The algorithm-specific MPIs that make up the signature itself
+    pub fn mpis(&self) -> &'a [u8] {
+        self.mpis
+    }
+
+    /// Checks whether two signatures are the same apart from the parts that
+    /// are not covered by the signature, and so can be changed by anyone:
+    /// the unhashed subpacket area of a version 4 signature, and the issuer
+    /// key ID of a version 3 signature.  Everything else is compared: the
+    /// version, signature type, algorithms, [`Self::hashed_material`],
+    /// [`Self::hash_prefix`], and [`Self::mpis`].
+    ///
+    /// This is for deduplicating signatures, for instance when merging
+    /// keyrings.  It does not check either signature.
+    ///
+    /// ```rust
+    /// # use openpgp_parser::{Reader, packet::next, signature::Signature};
+    /// let a = [0xC2, 12, 4, 0, 22, 8, 0, 0, 0, 0, 0x61, 0x58, 0, 1, 1];
+    /// let b = [0xC2, 15, 4, 0, 22, 8, 0, 0, 0, 3, 2, 7, 0, 0x61, 0x58, 0, 1, 1];
+    /// let a = Signature::parse(&next(&mut Reader::new(&a)).unwrap().unwrap()).unwrap();
+    /// let b = Signature::parse(&next(&mut Reader::new(&b)).unwrap().unwrap()).unwrap();
+    /// assert!(a.eq_signed_content(&b));
+    /// ```
+    pub fn eq_signed_content(&self, other: &Signature) -> bool {
+        self.version == other.version
+            && self.sig_type == other.sig_type
+            && self.pkey_alg == other.pkey_alg
+            && self.hash_alg == other.hash_alg
+            && self.hashed_material == other.hashed_material
+            && self.hash_prefix == other.hash_prefix
+            && self.mpis == other.mpis
+    }
+
+    /// For ECDSA and EdDSA signatures, returns the `r` and `s` components of
+    /// the signature as minimal big-endian integers.  Returns [`None`] for
+    /// other algorithms, or if the MPIs are not exactly two well-formed MPIs.
+    ///
+    /// Leading zero bytes are not part of an MPI, so the components may be
+    /// shorter than the field size.  For Ed25519, left-pad each of them to 32
+    /// bytes, and concatenate them to get the 64-byte signature.
+    pub fn ec_signature_components(&self) -> Option<(&'a [u8], &'a [u8])> {
+        match PublicKeyAlgorithm::from_u8(self.pkey_alg) {
+            PublicKeyAlgorithm::Ecdsa | PublicKeyAlgorithm::EdDsa => {}
+            _ => return None,
+        }
+        Reader::read_all_strict(self.mpis, |reader| Ok((reader.mpi()?, reader.mpi()?))).ok()
+    }
+
+    /// For DSA signatures, returns the `r` and `s` components of the
+    /// signature as minimal big-endian integers.  Returns [`None`] for other
+    /// algorithms, or if the MPIs are not exactly two well-formed MPIs.
+    ///
+    /// Verifying the signature also needs the DSA domain parameters `p`, `q`,
+    /// and `g`, which are the first three MPIs of the key packet (see
+    /// [`super::key::PublicKey::mpis`]).
+    pub fn dsa_signature_components(&self) -> Option<(&'a [u8], &'a [u8])> {
+        if PublicKeyAlgorithm::from_u8(self.pkey_alg) != PublicKeyAlgorithm::Dsa {
+            return None;
+        }
+        Reader::read_all_strict(self.mpis, |reader| Ok((reader.mpi()?, reader.mpi()?))).ok()
+    }
+
+    /// For RSA signatures, returns the signature integer as a big-endian
+    /// buffer, left-padded with zeros to `modulus_len` bytes, as many
+    /// cryptographic libraries require.  Returns [`None`] if this is not an
+    /// RSA signature, if the MPIs are not a single well-formed MPI, or if the
+    /// MPI is longer than `modulus_len`.
+    #[cfg(feature = "alloc")]
+    pub fn rsa_signature_bytes(&self, modulus_len: usize) -> Option<alloc::vec::Vec<u8>> {
+        match PublicKeyAlgorithm::from_u8(self.pkey_alg) {
+            PublicKeyAlgorithm::Rsa | PublicKeyAlgorithm::RsaSign => {}
+            _ => return None,
+        }
+        let mpi = Reader::read_all_strict(self.mpis, read_mpi).ok()?;
+        if mpi.len() > modulus_len {
+            return None;
+        }
+        let mut buf = alloc::vec::Vec::with_capacity(modulus_len);
+        buf.resize(modulus_len - mpi.len(), 0);
+        buf.extend_from_slice(mpi);
+        Some(buf)
+    }
+
+    /// Parses the embedded signature subpackets (type 32) of this signature,
+    /// from both subpacket areas.  The primary-key binding signature of a
+    /// signing subkey is stored this way.
+    ///
+    /// Each embedded signature is parsed with [`Self::parse`], and so are any
+    /// signatures embedded in those, up to [`MAX_EMBEDDING_DEPTH`] levels
+    /// deep.  Deeper nesting results in [`Error::RecursionLimit`].  Only
+    /// the signatures embedded directly in this one are returned.
+    #[cfg(feature = "alloc")]
+    pub fn embedded_signatures(&self) -> Result<alloc::vec::Vec<Signature<'a>>, Error> {
+        self.embedded_signatures_with_options(&super::ParseOptions::default())
+    }
+
+    /// Same as [`Self::embedded_signatures`], except that signatures may be
+    /// nested up to `options.max_depth` levels deep.  With a `max_depth` of 0,
+    /// any embedded signature results in [`Error::RecursionLimit`].
+    #[cfg(feature = "alloc")]
+    pub fn embedded_signatures_with_options(
+        &self,
+        options: &super::ParseOptions,
+    ) -> Result<alloc::vec::Vec<Signature<'a>>, Error> {
+        self.embedded_signatures_at_depth(1, options.max_depth)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn embedded_signatures_at_depth(
+        &self,
+        depth: usize,
+        max_depth: usize,
+    ) -> Result<alloc::vec::Vec<Signature<'a>>, Error> {
+        let mut sigs = alloc::vec::Vec::new();
+        for subpacket in SubpacketIter::new(self.hashed_subpackets)
+            .chain(SubpacketIter::new(self.unhashed_subpackets))
+        {
+            let body = match subpacket? {
+                (_, SUBPACKET_EMBEDDED_SIGNATURE, body) => body,
+                _ => continue,
+            };
+            if depth > max_depth {
+                return Err(Error::RecursionLimit);
+            }
+            let sig = Signature::parse(&packet::Packet::new(2, body)?)?;
+            sig.embedded_signatures_at_depth(depth + 1, max_depth)?;
+            sigs.push(sig)
+        }
+        Ok(sigs)
+    }
+}
+
+/// The maximum nesting depth of embedded signatures accepted by
+/// [`Signature::embedded_signatures`]
+pub const MAX_EMBEDDING_DEPTH: usize = 4;
+
+/// Collects the key IDs of the keys that made the signatures in `reader`, to
+/// find out which keys need to be loaded to check them.  Every issuer key ID
+/// subpacket (type 16) and version 4 issuer fingerprint subpacket (type 33)
+/// in either subpacket area is used, as is the key ID of version 3
+/// signatures.  Packets other than signatures are skipped.  Each key ID is
+/// only returned once, in the order they are first found.
+///
+/// Parse errors, including malformed signatures, are returned as is, and
+/// leave `reader` unchanged.  Otherwise, all of `reader` is consumed.
+///
+/// ```rust
+/// # use openpgp_parser::{Reader, signature::issuer_key_ids};
+/// let data = [
+///     0xCB, 0, 0xC2, 23, 4, 0, 22, 8, 0, 0, 0, 10, 9, 16, 1, 2, 3, 4, 5, 6, 7, 8,
+///     0x61, 0x58, 0, 1, 1,
+/// ];
+/// assert_eq!(issuer_key_ids(&mut Reader::new(&data)).unwrap(), [[1, 2, 3, 4, 5, 6, 7, 8]]);
+/// ```
+#[cfg(feature = "alloc")]
+pub fn issuer_key_ids(reader: &mut Reader) -> Result<alloc::vec::Vec<[u8; 8]>, Error> {
+    let mut key_ids = alloc::vec::Vec::new();
+    for packet in packet::PacketIter::new(*reader) {
+        let packet = packet?;
+        if packet.tag() != 2 {
+            continue;
+        }
+        let sig = Signature::parse(&packet)?;
+        let mut add = |key_id: [u8; 8]| {
+            if !key_ids.contains(&key_id) {
+                key_ids.push(key_id)
+            }
+        };
+        if let Some((_, key_id)) = sig.v3_fields {
+            add(key_id)
+        }
+        for subpacket in SubpacketIter::new(sig.hashed_subpackets)
+            .chain(SubpacketIter::new(sig.unhashed_subpackets))
+        {
+            let id = match subpacket? {
+                (_, SUBPACKET_ISSUER_KEYID, body) if body.len() == 8 => body,
+                (_, SUBPACKET_FINGERPRINT, body) if body.len() == 21 && body[0] == 4 => &body[13..],
+                _ => continue,
+            };
+            let mut key_id = [0u8; 8];
+            key_id.copy_from_slice(id);
+            add(key_id)
+        }
+    }
+    let len = reader.len();
+    reader.skip(len)?;
+    Ok(key_ids)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     static EDDSA_SIG: &'static [u8] = include_bytes!("../../data/eddsa.asc");
+    static ED25519_KEY: &[u8] = include_bytes!("../../data/ed25519-key.gpg");
+    static V3_SIG: &[u8] = include_bytes!("../../data/v3-rsa.sig");
     #[test]
     fn signature_not_valid_yet() {
         assert_eq!(
@@ -541,7 +1445,7 @@ mod tests {
         static TRAILING_JUNK: &'static [u8] = include_bytes!("../../data/trailing-junk.asc");
         assert_eq!(TRAILING_JUNK.len(), EDDSA_SIG.len() + 1);
         assert_eq!(
-            Reader::read_all(TRAILING_JUNK, Error::TrailingJunk, |r| read_signature(
+            Reader::read_all_strict(TRAILING_JUNK, |r| read_signature(
                 r,
                 0,
                 AllowWeakHashes::No,
@@ -549,7 +1453,9 @@ mod tests {
             )
             .map(drop))
             .unwrap_err(),
-            Error::TrailingJunk
+            Error::TrailingJunk {
+                offset: EDDSA_SIG.len()
+            }
         );
         assert_eq!(
             read_signature(
@@ -559,7 +1465,10 @@ mod tests {
                 SignatureType::Binary,
             )
             .unwrap_err(),
-            Error::PrematureEOF
+            Error::Truncated {
+                needed: 117,
+                available: 116
+            }
         );
         assert_eq!(
             read_signature(
@@ -586,11 +1495,644 @@ mod tests {
         assert!(sig.expiration_time.is_none());
         assert_eq!(sig.fingerprint.unwrap()[12..], sig.key_id[..]);
     }
+    /// Builds a version 4 Ed25519 signature packet with the given subpacket
+    /// areas, which must be short.  The signature itself is bogus.
+    fn sig_with_subpackets(hashed: &[u8], unhashed: &[u8]) -> Vec<u8> {
+        let mut body = vec![4, 0, 22, 8, 0, hashed.len() as u8];
+        body.extend_from_slice(hashed);
+        body.extend_from_slice(&[0, unhashed.len() as u8]);
+        body.extend_from_slice(unhashed);
+        body.extend_from_slice(&[0x61, 0x58, 0, 1, 1]);
+        let mut packet = vec![0xC2, body.len() as u8];
+        packet.extend_from_slice(&body);
+        packet
+    }
+
+    /// Parses `packet`, which must hold a single valid signature packet
+    fn parse_sig<'a>(packet: &'a [u8]) -> Signature<'a> {
+        Signature::parse(&packet::next(&mut Reader::new(packet)).unwrap().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn signer_user_id() {
+        let uid = b"Test Key <test@example.com>";
+        let mut subpacket = vec![uid.len() as u8 + 1, SUBPACKET_SIGNER_USER_ID];
+        subpacket.extend_from_slice(uid);
+        let sig = sig_with_subpackets(&subpacket, &[]);
+        let sig = parse_sig(&sig);
+        assert_eq!(sig.signer_user_id(), Some("Test Key <test@example.com>"));
+        assert_eq!(sig.signer_user_id_bytes(), Some(&uid[..]));
+        // only the hashed area counts
+        let sig = sig_with_subpackets(&[], &subpacket);
+        let sig = parse_sig(&sig);
+        assert_eq!(sig.signer_user_id(), None);
+        assert_eq!(sig.signer_user_id_bytes(), None);
+        // invalid UTF-8 is only available as bytes
+        let latin1 = [3, SUBPACKET_SIGNER_USER_ID, b'J', 0xFC];
+        let sig = sig_with_subpackets(&latin1, &[]);
+        let sig = parse_sig(&sig);
+        assert_eq!(sig.signer_user_id(), None);
+        assert_eq!(sig.signer_user_id_bytes(), Some(&[b'J', 0xFC][..]));
+        // an empty user ID is still present
+        let sig = sig_with_subpackets(&[1, SUBPACKET_SIGNER_USER_ID], &[]);
+        assert_eq!(parse_sig(&sig).signer_user_id(), Some(""));
+        assert_eq!(parse_sig(EDDSA_SIG).signer_user_id(), None);
+    }
+
+    #[test]
+    fn eq_signed_content() {
+        let creation = [5, SUBPACKET_CREATION_TIME, 0x60, 0x0F, 0x77, 0x1A];
+        let issuer = [9, SUBPACKET_ISSUER_KEYID, 1, 2, 3, 4, 5, 6, 7, 8];
+        let plain = sig_with_subpackets(&creation, &[]);
+        let with_issuer = sig_with_subpackets(&creation, &issuer);
+        let sig = parse_sig(&plain);
+        assert!(sig.eq_signed_content(&sig));
+        assert!(sig.eq_signed_content(&parse_sig(&with_issuer)));
+        assert!(parse_sig(&with_issuer).eq_signed_content(&sig));
+        let mut other_issuer = with_issuer.clone();
+        let len = other_issuer.len();
+        other_issuer[len - 6] = 9;
+        assert!(sig.eq_signed_content(&parse_sig(&other_issuer)));
+        // changes to anything but the unhashed area matter
+        let mut changes: Vec<Vec<u8>> = vec![
+            sig_with_subpackets(&creation[..0], &[]),
+            sig_with_subpackets(&issuer, &creation),
+        ];
+        for &(offset, value) in &[(3, 0x01), (4, 1), (5, 10), (13, 0x1B), (16, 0x59), (20, 3)] {
+            let mut changed = plain.clone();
+            changed[offset] = value;
+            changes.push(changed);
+        }
+        for changed in &changes {
+            assert!(!sig.eq_signed_content(&parse_sig(changed)), "{:?}", changed);
+            assert!(!parse_sig(changed).eq_signed_content(&sig));
+        }
+        // the key ID of a version 3 signature is not signed either
+        let mut v3 = V3_SIG.to_vec();
+        v3[10..18].copy_from_slice(&[0x55; 8]);
+        assert!(parse_sig(V3_SIG).eq_signed_content(&parse_sig(&v3)));
+        v3[9] ^= 1;
+        assert!(!parse_sig(V3_SIG).eq_signed_content(&parse_sig(&v3)));
+        assert!(!parse_sig(V3_SIG).eq_signed_content(&sig));
+    }
+
+    #[test]
+    fn expiration() {
+        let creation = [5, SUBPACKET_CREATION_TIME, 0x60, 0x0F, 0x77, 0x1A];
+        let packet = sig_with_subpackets(&creation, &[]);
+        let sig = parse_sig(&packet);
+        assert_eq!(sig.expiration(), None);
+        assert!(!sig.is_expired(!0));
+        let with_expiration = |expiration: u32| {
+            let mut hashed = creation.to_vec();
+            hashed.extend_from_slice(&[5, SUBPACKET_SIG_EXPIRATION_TIME]);
+            hashed.extend_from_slice(&expiration.to_be_bytes());
+            sig_with_subpackets(&hashed, &[])
+        };
+        for &(expiration, now, expected) in &[
+            (0, !0, false),
+            (3600, 0x600F771A, false),
+            (3600, 0x600F771A + 3599, false),
+            (3600, 0x600F771A + 3600, true),
+            (3600, !0, true),
+            (!0, !0, false),
+        ] {
+            let packet = with_expiration(expiration);
+            let sig = parse_sig(&packet);
+            assert_eq!(sig.expiration(), Some(expiration));
+            assert_eq!(sig.is_expired(now), expected);
+        }
+        // an expiration time without a creation time
+        let packet = sig_with_subpackets(&[5, SUBPACKET_SIG_EXPIRATION_TIME, 0, 0, 0, 1], &[]);
+        let sig = parse_sig(&packet);
+        assert!(sig.is_expired(0));
+    }
+
+    #[test]
+    fn key_flags() {
+        for &(start, end, flags) in &[(82, 228, 0x03), (286, 408, 0x0C)] {
+            let sig = parse_sig(&ED25519_KEY[start..end]);
+            assert_eq!(sig.key_flags(), Some(flags));
+            assert_eq!(sig.can_sign(), flags & KEY_FLAG_SIGN != 0);
+            assert_eq!(sig.can_certify(), flags & KEY_FLAG_CERTIFY != 0);
+        }
+        // empty and missing key flags
+        for hashed in &[&[1, SUBPACKET_KEY_FLAGS][..], &[]] {
+            let packet = sig_with_subpackets(hashed, &[]);
+            let sig = parse_sig(&packet);
+            assert_eq!(sig.key_flags(), None);
+            assert!(!sig.can_sign());
+            assert!(!sig.can_certify());
+        }
+        // key flags in the unhashed area are ignored
+        let packet = sig_with_subpackets(&[], &[2, SUBPACKET_KEY_FLAGS, KEY_FLAG_SIGN]);
+        assert_eq!(parse_sig(&packet).key_flags(), None);
+    }
+
+    #[test]
+    fn revocation_reason() {
+        for &(hashed, expected) in &[
+            (
+                &[
+                    2,
+                    SUBPACKET_REVOCATION_REASON,
+                    REVOCATION_REASON_COMPROMISED,
+                ][..],
+                Some((REVOCATION_REASON_COMPROMISED, &[][..])),
+            ),
+            (
+                &[5, SUBPACKET_REVOCATION_REASON, 0x80, b'o', b'l', b'd'],
+                Some((0x80, &b"old"[..])),
+            ),
+            (&[1, SUBPACKET_REVOCATION_REASON], None),
+            (&[], None),
+        ] {
+            let mut packet = sig_with_subpackets(hashed, &[]);
+            // key revocation
+            packet[3] = 0x20;
+            let sig = parse_sig(&packet);
+            assert_eq!(sig.revocation_reason(), expected);
+        }
+        // a reason in the unhashed area is ignored
+        let packet = sig_with_subpackets(&[], &[2, SUBPACKET_REVOCATION_REASON, 1]);
+        assert_eq!(parse_sig(&packet).revocation_reason(), None);
+    }
+
+    #[test]
+    fn hard_revocation() {
+        for &(sig_type, reason, revocation, hard) in &[
+            (0x20, None, true, true),
+            (0x20, Some(REVOCATION_REASON_UNSPECIFIED), true, true),
+            (0x20, Some(REVOCATION_REASON_SUPERSEDED), true, false),
+            (0x20, Some(REVOCATION_REASON_COMPROMISED), true, true),
+            (0x28, Some(REVOCATION_REASON_RETIRED), true, false),
+            (0x28, Some(REVOCATION_REASON_COMPROMISED), true, true),
+            (0x28, Some(REVOCATION_REASON_USER_ID_INVALID), true, true),
+            (0x28, Some(100), true, true),
+            (0x30, None, true, false),
+            (0x30, Some(REVOCATION_REASON_COMPROMISED), true, false),
+            (0x30, Some(REVOCATION_REASON_USER_ID_INVALID), true, false),
+            (0x00, Some(REVOCATION_REASON_COMPROMISED), false, false),
+            (0x13, None, false, false),
+            (0x1F, Some(REVOCATION_REASON_COMPROMISED), false, false),
+        ] {
+            let mut packet = match reason {
+                Some(code) => sig_with_subpackets(&[2, SUBPACKET_REVOCATION_REASON, code], &[]),
+                None => sig_with_subpackets(&[], &[]),
+            };
+            packet[3] = sig_type;
+            let sig = parse_sig(&packet);
+            assert_eq!(sig.is_revocation(), revocation);
+            assert_eq!(sig.is_hard_revocation(), hard);
+        }
+    }
+
+    #[test]
+    fn preferred_hash_algorithms() {
+        let sig = parse_sig(&ED25519_KEY[82..228]);
+        assert_eq!(
+            sig.preferred_hash_algorithms(),
+            Some(&[10, 9, 8, 11, 2][..])
+        );
+        // the subkey binding signature has no preferences
+        let sig = parse_sig(&ED25519_KEY[286..408]);
+        assert_eq!(sig.preferred_hash_algorithms(), None);
+        let packet = sig_with_subpackets(&[1, SUBPACKET_PREFERRED_HASH], &[3, 21, 8, 10]);
+        let sig = parse_sig(&packet);
+        assert_eq!(sig.preferred_hash_algorithms(), Some(&[][..]));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn issuer_key_ids() {
+        let mut data = ED25519_KEY.to_vec();
+        data.extend_from_slice(EDDSA_SIG);
+        data.extend_from_slice(V3_SIG);
+        data.extend_from_slice(EDDSA_SIG);
+        let mut reader = Reader::new(&data);
+        assert_eq!(
+            super::issuer_key_ids(&mut reader).unwrap(),
+            [
+                *b"\xE1\xB5\xE5\xB1\x30\xA4\xB4\x61",
+                *b"\x28\xA4\x5C\x93\xB0\xB5\xB6\xE0",
+                *b"\x9E\x9B\x9A\xE4\x14\x5C\x30\x64",
+            ]
+        );
+        assert!(reader.is_empty());
+        assert_eq!(reader.offset(), data.len());
+        let mut reader = Reader::new_at(&data, 100);
+        super::issuer_key_ids(&mut reader).unwrap();
+        assert_eq!(reader.offset(), 100 + data.len());
+        // a key ID subpacket that disagrees with the fingerprint
+        let mut unhashed = vec![9, SUBPACKET_ISSUER_KEYID];
+        unhashed.extend_from_slice(&[1; 8]);
+        let mut hashed = vec![22, SUBPACKET_FINGERPRINT, 4];
+        hashed.extend_from_slice(&[2; 20]);
+        let packet = sig_with_subpackets(&hashed, &unhashed);
+        assert_eq!(
+            super::issuer_key_ids(&mut Reader::new(&packet)).unwrap(),
+            [[2; 8], [1; 8]]
+        );
+        // errors leave the reader alone
+        let mut reader = Reader::new(&data[..data.len() - 1]);
+        assert_eq!(
+            super::issuer_key_ids(&mut reader).unwrap_err(),
+            Error::Truncated {
+                needed: 117,
+                available: 116
+            }
+        );
+        assert_eq!(reader.len(), data.len() - 1);
+        assert!(super::issuer_key_ids(&mut Reader::empty())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn notations() {
+        let notation = |flags: u8, name: &[u8], value: &[u8]| {
+            let mut sub = vec![0, SUBPACKET_NOTATION, flags, 0, 0, 0];
+            sub.extend_from_slice(&[0, name.len() as u8, 0, value.len() as u8]);
+            sub.extend_from_slice(name);
+            sub.extend_from_slice(value);
+            sub[0] = sub.len() as u8 - 1;
+            sub
+        };
+        let mut hashed = notation(0x80, b"policy@example.com", b"strict");
+        hashed.extend_from_slice(&[5, SUBPACKET_CREATION_TIME, 0x60, 0x0F, 0x77, 0x1A]);
+        hashed.extend_from_slice(&notation(0, b"binary@example.com", &[0, 0xFF]));
+        let unhashed = notation(0x80, b"unhashed@example.com", b"");
+        let packet = sig_with_subpackets(&hashed, &unhashed);
+        let sig = parse_sig(&packet);
+        let notations: Vec<_> = sig.notations().map(Result::unwrap).collect();
+        assert_eq!(
+            notations,
+            [
+                (true, &b"policy@example.com"[..], &b"strict"[..]),
+                (false, &b"binary@example.com"[..], &[0, 0xFF][..]),
+            ]
+        );
+        assert_eq!(sig.creation_time(), Some(0x600F771A));
+        // lengths that do not match the subpacket
+        for &(name_len, value_len) in &[(1, 0), (0, 0), (2, 2), (0xFF, 1)] {
+            let mut bad = notation(0x80, b"a", b"b");
+            bad[7] = name_len;
+            bad[9] = value_len;
+            let packet = sig_with_subpackets(&bad, &[]);
+            let sig = parse_sig(&packet);
+            let mut iter = sig.notations();
+            assert_eq!(iter.next(), Some(Err(Error::IllFormedSignature)));
+            assert_eq!(iter.next(), None);
+        }
+        // too short for the flags and lengths
+        let packet = sig_with_subpackets(&[4, SUBPACKET_NOTATION, 0, 0, 0], &[]);
+        let sig = parse_sig(&packet);
+        assert_eq!(sig.notations().next(), Some(Err(Error::IllFormedSignature)));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn embedded_signatures() {
+        // Returns the body of a signature with `hashed` as its hashed area
+        let body = |hashed: &[u8]| sig_with_subpackets(hashed, &[])[2..].to_vec();
+        let embed = |body: &[u8]| {
+            let mut sub = vec![body.len() as u8 + 1, SUBPACKET_EMBEDDED_SIGNATURE];
+            sub.extend_from_slice(body);
+            sub
+        };
+        let creation = [5, SUBPACKET_CREATION_TIME, 0x60, 0x0F, 0x77, 0x1A];
+        let inner = body(&creation);
+        let mut hashed = embed(&inner);
+        hashed.extend_from_slice(&creation);
+        let packet = sig_with_subpackets(&hashed, &embed(&inner));
+        let sig = parse_sig(&packet);
+        let embedded = sig.embedded_signatures().unwrap();
+        assert_eq!(embedded.len(), 2);
+        assert_eq!(embedded[0].creation_time(), Some(0x600F771A));
+        assert!(embedded[1].embedded_signatures().unwrap().is_empty());
+        // nesting up to the limit is fine, but not beyond it
+        let mut nested = inner;
+        for depth in 1..MAX_EMBEDDING_DEPTH + 2 {
+            nested = body(&embed(&nested));
+            let packet = packet::Packet::new(2, &nested).unwrap();
+            let result = Signature::parse(&packet).unwrap().embedded_signatures();
+            if depth <= MAX_EMBEDDING_DEPTH {
+                assert_eq!(result.unwrap().len(), 1);
+            } else {
+                assert_eq!(result.unwrap_err(), Error::RecursionLimit);
+            }
+            for max_depth in 0..MAX_EMBEDDING_DEPTH + 2 {
+                let options = super::super::ParseOptions {
+                    max_depth,
+                    ..Default::default()
+                };
+                let result = Signature::parse(&packet)
+                    .unwrap()
+                    .embedded_signatures_with_options(&options);
+                if depth <= max_depth {
+                    assert_eq!(result.unwrap().len(), 1);
+                } else {
+                    assert_eq!(result.unwrap_err(), Error::RecursionLimit);
+                }
+            }
+        }
+        // a bad embedded signature
+        let packet = sig_with_subpackets(&embed(&[4, 0]), &[]);
+        let sig = parse_sig(&packet);
+        assert_eq!(
+            sig.embedded_signatures().unwrap_err(),
+            Error::Truncated {
+                needed: 1,
+                available: 0
+            }
+        );
+    }
+
+    #[test]
+    fn creation_time_is_hashed() {
+        let mut body = vec![4, 0, 22, 8, 0, 6, 5, SUBPACKET_CREATION_TIME, 0x80, 0, 0, 1];
+        body.extend_from_slice(&[0, 6, 5, SUBPACKET_CREATION_TIME, 0x60, 0x0F, 0x77, 0x1A]);
+        body.extend_from_slice(&[0x61, 0x58, 0, 1, 1]);
+        let mut packet = vec![0xC2, body.len() as u8];
+        packet.extend_from_slice(&body);
+        // past 2038 as a signed 32-bit value
+        assert_eq!(parse_sig(&packet).creation_time(), Some(0x8000_0001));
+        // only in the unhashed area
+        packet[9] = 101;
+        assert_eq!(parse_sig(&packet).creation_time(), None);
+        // wrong length
+        packet[9] = SUBPACKET_CREATION_TIME;
+        packet[8] = 3;
+        packet[12] = 1;
+        packet[13] = 101;
+        assert_eq!(parse_sig(&packet).creation_time(), None);
+    }
+
+    #[test]
+    fn parses_v3_signature() {
+        let key_id = *b"\x9E\x9B\x9A\xE4\x14\x5C\x30\x64";
+        let sig = parse_sig(V3_SIG);
+        assert_eq!(sig.version(), 3);
+        assert_eq!(sig.signature_type(), SignatureType::Binary.to_u8());
+        assert_eq!(sig.pk_algorithm(), PublicKeyAlgorithm::Rsa.to_u8());
+        assert_eq!(i32::from(sig.hash_algorithm()), OPENPGP_HASH_SHA256);
+        assert_eq!(sig.creation_time(), Some(1611626266));
+        assert_eq!(sig.issuer_key_id(), Some(key_id));
+        assert!(sig.issuer_fingerprint().is_none());
+        assert!(sig.hashed_subpackets().is_empty());
+        assert!(sig.unhashed_subpackets().is_empty());
+        assert_eq!(sig.hash_prefix(), [0xC7, 0xF4]);
+        assert_eq!(sig.hashed_material(), &V3_SIG[5..10]);
+        assert_eq!(sig.hashed_material()[1..], 1611626266u32.to_be_bytes());
+        assert!(sig.trailer().is_none());
+        assert_eq!(sig.mpis().len(), 2 + 256);
+        #[cfg(feature = "alloc")]
+        {
+            let padded = sig.rsa_signature_bytes(256).unwrap();
+            assert_eq!(padded, &sig.mpis()[2..]);
+            assert!(sig.rsa_signature_bytes(255).is_none());
+            // a short MPI is padded
+            let mut short = V3_SIG[..V3_SIG.len() - 258].to_owned();
+            short.extend_from_slice(&[0, 9, 1, 0xFF]);
+            short[1..3].copy_from_slice(&[0, 23]);
+            let sig = parse_sig(&short);
+            assert_eq!(sig.rsa_signature_bytes(4).unwrap(), [0, 0, 1, 0xFF]);
+            assert_eq!(sig.rsa_signature_bytes(2).unwrap(), [1, 0xFF]);
+            assert!(sig.rsa_signature_bytes(1).is_none());
+        }
+        let info = read_signature(
+            &mut Reader::new(V3_SIG),
+            0,
+            AllowWeakHashes::No,
+            SignatureType::Binary,
+        )
+        .unwrap();
+        assert_eq!(info.key_id, key_id);
+        assert_eq!(info.creation_time, 1611626266);
+        assert!(info.fingerprint.is_none());
+    }
+
+    #[test]
+    fn signature_type_mapping() {
+        for ty in 0..=255u8 {
+            let sig_type = SignatureType::from_u8(ty);
+            assert_eq!(sig_type.to_u8(), ty);
+            let unknown = format!("{:?}", sig_type) == format!("Unknown({})", ty);
+            match ty {
+                0...2 | 0x10...0x13 | 0x18 | 0x19 | 0x1F | 0x20 | 0x28 | 0x30 | 0x40 => {
+                    assert!(!unknown)
+                }
+                _ => assert!(unknown),
+            }
+            // equality is by value, even for an Unknown that from_u8 would
+            // not produce
+            assert_eq!(SignatureType::Unknown(ty), sig_type);
+            assert_eq!(sig_type.is_certification(), (0x10..=0x13).contains(&ty));
+            assert_eq!(sig_type.is_document(), ty <= 1);
+            assert_eq!(
+                sig_type.is_revocation(),
+                ty == 0x20 || ty == 0x28 || ty == 0x30
+            );
+        }
+        // every named variant survives the round trip through its value
+        for &sig_type in &[
+            SignatureType::Binary,
+            SignatureType::Text,
+            SignatureType::Standalone,
+            SignatureType::GenericCert,
+            SignatureType::PersonaCert,
+            SignatureType::CasualCert,
+            SignatureType::PositiveCert,
+            SignatureType::SubkeyBinding,
+            SignatureType::PrimaryKeyBinding,
+            SignatureType::KeySig,
+            SignatureType::PrimaryKeyRevocation,
+            SignatureType::SubkeyRevocation,
+            SignatureType::CertificationRevocatin,
+            SignatureType::Timestamp,
+        ] {
+            let round_trip = SignatureType::from_u8(sig_type.to_u8());
+            assert_eq!(format!("{:?}", round_trip), format!("{:?}", sig_type));
+        }
+        assert_ne!(SignatureType::Binary, SignatureType::Text);
+    }
+
+    #[test]
+    fn hashed_material() {
+        let packet = packet::next(&mut Reader::new(&ED25519_KEY[82..228]))
+            .unwrap()
+            .unwrap();
+        let sig = Signature::parse(&packet).unwrap();
+        assert_eq!(sig.hashed_material(), &packet.contents()[..62]);
+        assert_eq!(sig.hashed_material()[6..], *sig.hashed_subpackets());
+        assert_eq!(sig.trailer(), Some([4, 0xFF, 0, 0, 0, 62]));
+        // SHA-256 of 0x99, the key, 0xB4, the user ID, the hashed material,
+        // and the trailer, computed externally
+        let digest = [
+            0x38, 0x2E, 0xEB, 0xDC, 0xC2, 0x67, 0x93, 0xB8, 0xD8, 0xEB, 0x78, 0x7B, 0x0C, 0xD3,
+            0x1B, 0xDC, 0x6E, 0x02, 0x71, 0xAD, 0xAB, 0x96, 0x9A, 0x27, 0x68, 0x14, 0xAC, 0x08,
+            0x1B, 0x29, 0x14, 0x4E,
+        ];
+        assert!(sig.quick_check(&digest));
+    }
+
+    #[test]
+    fn parses_signature_struct() {
+        let sig = parse_sig(EDDSA_SIG);
+        assert_eq!(sig.version(), 4);
+        assert_eq!(sig.signature_type(), SignatureType::Binary.to_u8());
+        assert_eq!(sig.pk_algorithm(), PublicKeyAlgorithm::EdDsa.to_u8());
+        assert_eq!(i32::from(sig.hash_algorithm()), OPENPGP_HASH_SHA256);
+        assert_eq!(sig.creation_time(), Some(1611626266));
+        assert_eq!(sig.hashed_subpackets(), &EDDSA_SIG[8..37]);
+        assert_eq!(sig.unhashed_subpackets(), &EDDSA_SIG[39..49]);
+        assert_eq!(sig.hash_prefix(), [0x61, 0x58]);
+        assert!(sig.quick_check(&[0x61, 0x58, 0x01]));
+        #[cfg(feature = "alloc")]
+        assert!(sig.rsa_signature_bytes(64).is_none());
+        let (r, s) = sig.ec_signature_components().unwrap();
+        assert_eq!(r, &EDDSA_SIG[53..85]);
+        assert_eq!(s, &EDDSA_SIG[87..]);
+        assert!(r.len() <= 32 && s.len() <= 32);
+        let mut mpis = sig.clone();
+        mpis.mpis = &EDDSA_SIG[51..85];
+        assert!(mpis.ec_signature_components().is_none());
+        mpis.mpis = &EDDSA_SIG[51..EDDSA_SIG.len() - 1];
+        assert!(mpis.ec_signature_components().is_none());
+        assert!(sig.dsa_signature_components().is_none());
+        assert_eq!(
+            sig.issuer_fingerprint(),
+            Some([
+                0xE8, 0x3A, 0x38, 0x0B, 0x85, 0x75, 0x56, 0x2B, 0x3C, 0x6F, 0x41, 0xCA, 0x28, 0xA4,
+                0x5C, 0x93, 0xB0, 0xB5, 0xB6, 0xE0
+            ])
+        );
+        assert_eq!(
+            sig.issuer_key_id(),
+            Some([0x28, 0xA4, 0x5C, 0x93, 0xB0, 0xB5, 0xB6, 0xE0])
+        );
+        assert!(sig.quick_check(&[0x61, 0x58]));
+        assert!(!sig.quick_check(&[0x58, 0x61, 0x01]));
+        assert!(!sig.quick_check(&[0x61]));
+        assert!(!sig.quick_check(&[]));
+        assert_eq!(sig.mpis(), &EDDSA_SIG[51..]);
+        // no MPIs at all
+        let mut s = EDDSA_SIG[..51].to_owned();
+        s[1] = 49;
+        let packet = packet::next(&mut Reader::new(&s)).unwrap().unwrap();
+        assert_eq!(
+            Signature::parse(&packet).unwrap_err(),
+            Error::Truncated {
+                needed: 1,
+                available: 0
+            }
+        );
+        let mut s = EDDSA_SIG.to_owned();
+        for &(version, err) in &[
+            (2, Error::UnsupportedSignatureVersion(2)),
+            (5, Error::UnsupportedSignatureVersion(5)),
+            (6, Error::UnsupportedSignatureVersion(6)),
+            (3, Error::IllFormedSignature),
+        ] {
+            s[2] = version;
+            let packet = packet::next(&mut Reader::new(&s)).unwrap().unwrap();
+            assert_eq!(Signature::parse(&packet).unwrap_err(), err);
+        }
+        let packet = packet::next(&mut Reader::new(&[0xC2, 2, 4, 0]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Signature::parse(&packet).unwrap_err(),
+            Error::Truncated {
+                needed: 1,
+                available: 0
+            }
+        );
+        let packet = packet::next(&mut Reader::new(&[0xC6, 1, 4]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            Signature::parse(&packet).unwrap_err(),
+            Error::IllFormedSignature
+        );
+    }
+
+    #[test]
+    fn dsa_signature_components() {
+        let mut sig = parse_sig(EDDSA_SIG);
+        // the MPIs have the same structure as for EdDSA
+        sig.pkey_alg = PublicKeyAlgorithm::Dsa.to_u8();
+        assert!(sig.ec_signature_components().is_none());
+        assert_eq!(
+            sig.dsa_signature_components(),
+            Some((&EDDSA_SIG[53..85], &EDDSA_SIG[87..]))
+        );
+        for &end in &[51, 85, EDDSA_SIG.len() - 1] {
+            sig.mpis = &EDDSA_SIG[51..end];
+            assert!(sig.dsa_signature_components().is_none());
+        }
+    }
+    #[test]
+    fn subpacket_iter() {
+        let sig = parse_sig(EDDSA_SIG);
+        let subpackets: Vec<_> = SubpacketIter::new(sig.hashed_subpackets())
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(subpackets.len(), 2);
+        let (critical, ty, fpr) = subpackets[0];
+        assert!(!critical);
+        assert_eq!(ty, SUBPACKET_FINGERPRINT);
+        assert_eq!(fpr[0], 4);
+        assert_eq!(fpr[13..], b"\x28\xA4\x5C\x93\xB0\xB5\xB6\xE0"[..]);
+        assert_eq!(
+            subpackets[1],
+            (false, SUBPACKET_CREATION_TIME, &b"\x60\x0f\x77\x1a"[..])
+        );
+        let mut iter = SubpacketIter::new(sig.unhashed_subpackets());
+        assert_eq!(iter.next().unwrap().unwrap().1, SUBPACKET_ISSUER_KEYID);
+        assert!(iter.next().is_none());
+        // only an issuer fingerprint
+        let mut s = EDDSA_SIG.to_owned();
+        s[40] = 101;
+        let sig = parse_sig(&s);
+        assert!(sig.issuer_fingerprint().is_some());
+        assert_eq!(sig.issuer_key_id().unwrap(), fpr[13..]);
+        // unknown fingerprint version
+        s[10] = 5;
+        let sig = parse_sig(&s);
+        assert!(sig.issuer_fingerprint().is_none());
+        assert!(sig.issuer_key_id().is_none());
+        // only an issuer key ID
+        s[40] = SUBPACKET_ISSUER_KEYID;
+        let sig = parse_sig(&s);
+        assert!(sig.issuer_fingerprint().is_none());
+        assert_eq!(sig.issuer_key_id().unwrap(), fpr[13..]);
+        // truncated length
+        let mut iter = SubpacketIter::new(&[5, 2, 0, 0]);
+        assert_eq!(
+            iter.next(),
+            Some(Err(Error::Truncated {
+                needed: 5,
+                available: 3
+            }))
+        );
+        assert_eq!(iter.next(), None);
+        let mut iter = SubpacketIter::new(&[0xE0, 1]);
+        assert!(iter.next().unwrap().is_err());
+        assert_eq!(iter.next(), None);
+    }
+
     #[test]
     fn mpi_too_short() {
         let mut buf: Reader = Reader::new(b"\x00\x09\xFF");
         assert_eq!(buf.len(), 3);
-        assert_eq!(read_mpi(&mut buf).unwrap_err(), Error::PrematureEOF);
+        assert_eq!(
+            read_mpi(&mut buf).unwrap_err(),
+            Error::Truncated {
+                needed: 2,
+                available: 1
+            }
+        );
         assert_eq!(buf.len(), 3);
     }
     #[test]
@@ -606,7 +2148,13 @@ mod tests {
                 read_mpi(&mut buf).unwrap();
                 assert_eq!(buf.len(), 0);
             } else if i > 16 {
-                assert_eq!(read_mpi(&mut buf).unwrap_err(), Error::PrematureEOF);
+                assert_eq!(
+                    read_mpi(&mut buf).unwrap_err(),
+                    Error::Truncated {
+                        needed: usize::from(u16::from(i) + 7) / 8,
+                        available: 2
+                    }
+                );
                 assert_eq!(buf.len(), 4);
             } else {
                 assert_eq!(read_mpi(&mut buf).unwrap_err(), Error::BadMPI);
@@ -614,14 +2162,45 @@ mod tests {
             }
         }
     }
+    #[test]
+    fn validate_mpi() {
+        for bits in 1..=16u16 {
+            let len = usize::from(bits + 7) / 8;
+            let top = 1u16 << ((bits - 1) % 8);
+            let mut mpi = [0xFFu8; 2];
+            mpi[0] = top as u8;
+            assert_eq!(super::validate_mpi(&mpi[..len], bits), Ok(()));
+            mpi[0] = (top * 2 - 1) as u8;
+            assert_eq!(super::validate_mpi(&mpi[..len], bits), Ok(()));
+            if bits % 8 != 1 {
+                mpi[0] = (top / 2) as u8;
+                assert_eq!(super::validate_mpi(&mpi[..len], bits), Err(Error::BadMPI));
+            }
+            // wrong length
+            assert_eq!(
+                super::validate_mpi(&[1, 0, 0][..len + 1], bits),
+                Err(Error::BadMPI)
+            );
+            assert_eq!(
+                super::validate_mpi(&mpi[..len - 1], bits),
+                Err(Error::BadMPI)
+            );
+        }
+        assert_eq!(super::validate_mpi(&[0], 0), Err(Error::BadMPI));
+        assert_eq!(super::validate_mpi(&[0], 1), Err(Error::BadMPI));
+    }
+
     #[test]
     fn wrong_signature_version() {
         for i in 0u16..256 {
             let i = i as u8;
             let e = if i == 3 || i == 4 {
-                Error::PrematureEOF
+                Error::Truncated {
+                    needed: 1,
+                    available: 0,
+                }
             } else {
-                Error::UnsupportedSignatureVersion
+                Error::UnsupportedSignatureVersion(i)
             };
             assert_eq!(
                 parse_packet_body(
@@ -0,0 +1,156 @@
+//! User ID and user attribute packets
+//!
+//! A user ID packet (tag 13) names the owner of a key, conventionally in the
+//! form `Name <email>`.  A user attribute packet (tag 17) serves the same
+//! purpose, but holds a sequence of subpackets instead of text; the only type
+//! defined by RFC 4880 is an image of the owner.  The functions here do not
+//! check the packet tag; use `cert::Cert::user_ids` or
+//! [`super::packet::PacketIter::of_tag`] to find the packets of a key.
+
+use super::packet::{get_varlen_bytes, Packet};
+use super::{Error, Reader};
+
+/// The user attribute subpacket type of an image
+pub const USER_ATTRIBUTE_IMAGE: u8 = 1;
+
+/// Gets the text of a user ID packet.  Returns [`Error::InvalidUtf8`] if it is
+/// not valid UTF-8.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, packet::Packet, user_id::parse_user_id};
+/// let packet = Packet::new(13, b"Test Key <test@example.com>").unwrap();
+/// assert_eq!(parse_user_id(&packet).unwrap(), "Test Key <test@example.com>");
+/// let packet = Packet::new(13, b"\xFF").unwrap();
+/// assert_eq!(parse_user_id(&packet).unwrap_err(), Error::InvalidUtf8);
+/// ```
+pub fn parse_user_id<'a>(packet: &Packet<'a>) -> Result<&'a str, Error> {
+    core::str::from_utf8(user_id_bytes(packet)).map_err(|_| Error::InvalidUtf8)
+}
+
+/// Gets the raw contents of a user ID packet, for callers that can handle
+/// text that is not valid UTF-8.
+pub fn user_id_bytes<'a>(packet: &Packet<'a>) -> &'a [u8] {
+    packet.contents()
+}
+
+/// An iterator over the subpackets of a user attribute packet, as returned by
+/// [`user_attribute_subpackets`].  Each item is a tuple of the subpacket type
+/// and the subpacket body.
+///
+/// If a subpacket is malformed, [`Error::IllFormedUserAttribute`] is yielded
+/// once, after which the iterator always returns [`None`].
+#[derive(Clone, Debug)]
+pub struct UserAttributeSubpackets<'a> {
+    reader: Reader<'a>,
+}
+
+impl<'a> Iterator for UserAttributeSubpackets<'a> {
+    type Item = Result<(u8, &'a [u8]), Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.reader.is_empty() {
+            return None;
+        }
+        // Every framing error, including a partial or oversized length, means
+        // the user attribute is malformed
+        let subpacket = get_varlen_bytes(&mut self.reader)
+            .map_err(|_| Error::IllFormedUserAttribute)
+            .and_then(|subpacket| match subpacket.split_first() {
+                Some((&ty, body)) => Ok((ty, body)),
+                None => Err(Error::IllFormedUserAttribute),
+            });
+        if subpacket.is_err() {
+            self.reader = Reader::empty();
+        }
+        Some(subpacket)
+    }
+}
+
+impl<'a> core::iter::FusedIterator for UserAttributeSubpackets<'a> {}
+
+/// Iterates over the subpackets of a user attribute packet.  Subpackets are
+/// framed like signature subpackets, but have no critical bit.  Nothing about
+/// the subpacket bodies is checked, so image data must be validated by the
+/// caller before it is used.
+///
+/// ```rust
+/// # use openpgp_parser::{Error, packet::Packet, user_id::user_attribute_subpackets};
+/// let packet = Packet::new(17, &[3, 1, 0xAB, 0xCD, 1, 100, 2, 1]).unwrap();
+/// let mut iter = user_attribute_subpackets(&packet);
+/// assert_eq!(iter.next(), Some(Ok((1, &[0xAB, 0xCD][..]))));
+/// assert_eq!(iter.next(), Some(Ok((100, &[][..]))));
+/// assert_eq!(iter.next(), Some(Err(Error::IllFormedUserAttribute)));
+/// assert_eq!(iter.next(), None);
+/// ```
+pub fn user_attribute_subpackets<'a>(packet: &Packet<'a>) -> UserAttributeSubpackets<'a> {
+    UserAttributeSubpackets {
+        reader: Reader::new(packet.contents()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::packet::next;
+    use super::super::Reader;
+    use super::*;
+
+    #[test]
+    fn parses_key_user_id() {
+        let key = include_bytes!("../../data/ed25519-key.gpg");
+        let packet = next(&mut Reader::new(&key[53..82])).unwrap().unwrap();
+        assert_eq!(packet.tag(), 13);
+        assert_eq!(parse_user_id(&packet), Ok("Test Key <test@example.com>"));
+        assert_eq!(user_id_bytes(&packet), b"Test Key <test@example.com>");
+        for &(bytes, ok) in &[
+            (&b""[..], true),
+            (&b"J\xC3\xBCrgen <j@example.com>"[..], true),
+            (&b"\xC3"[..], false),
+            (&b"\xED\xA0\x80"[..], false),
+            (&b"a\0b"[..], true),
+        ] {
+            let packet = Packet::new(13, bytes).unwrap();
+            assert_eq!(parse_user_id(&packet).is_ok(), ok);
+            assert_eq!(user_id_bytes(&packet), bytes);
+        }
+    }
+
+    #[test]
+    fn user_attribute_subpackets() {
+        // a JPEG image attribute: a 16-byte little-endian image header, then
+        // the image data
+        let mut image = vec![1, 0x10, 0, 1, 1];
+        image.resize(17, 0);
+        image.extend_from_slice(&[0xFF, 0xD8, 0xFF, 0xD9]);
+        let mut body = vec![image.len() as u8 + 1, USER_ATTRIBUTE_IMAGE];
+        body.extend_from_slice(&image);
+        // a private subpacket with a two-byte length
+        body.extend_from_slice(&[0xC0, 0x00, 105]);
+        body.resize(body.len() + 0xBF, 0x55);
+        let packet = Packet::new(17, &body).unwrap();
+        let subpackets: Vec<_> = super::user_attribute_subpackets(&packet)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(subpackets.len(), 2);
+        assert_eq!(subpackets[0], (USER_ATTRIBUTE_IMAGE, &image[..]));
+        assert_eq!(subpackets[1], (105, &[0x55; 0xBF][..]));
+        assert_eq!(
+            super::user_attribute_subpackets(&Packet::new(17, &[]).unwrap()).next(),
+            None
+        );
+        // empty, truncated, partial lengths, and lengths too long for any
+        // platform all give the same error
+        for bad in &[
+            &[0][..],
+            &[5, 1, 0],
+            &[0xC0],
+            &[0xFF, 0, 0, 0],
+            &[0xE0, 1],
+            &[0xFE, 1],
+            &[0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 1],
+        ] {
+            let packet = Packet::new(17, bad).unwrap();
+            let mut iter = super::user_attribute_subpackets(&packet);
+            assert_eq!(iter.next(), Some(Err(Error::IllFormedUserAttribute)));
+            assert_eq!(iter.next(), None);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Miscellaneous helpers
+
+/// Compares `a` and `b` for equality in constant time.  If the lengths differ,
+/// [`false`] is returned immediately; otherwise, every byte is examined,
+/// whether or not a difference has already been found.
+///
+/// This is for comparing values that are derived from secrets, such as
+/// digests, to ones that an attacker controls.  Key IDs and fingerprints are
+/// not secret, but comparing them this way costs nothing.  Note that this is
+/// a best-effort guarantee: Rust does not promise that the optimizer will
+/// leave the loop alone.
+///
+/// ```rust
+/// # use openpgp_parser::util::ct_eq;
+/// assert!(ct_eq(b"abc", b"abc"));
+/// assert!(!ct_eq(b"abc", b"abd"));
+/// assert!(!ct_eq(b"abc", b"ab"));
+/// assert!(ct_eq(b"", b""));
+/// ```
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (x, y) in a.iter().zip(b) {
+        diff |= x ^ y
+    }
+    diff == 0
+}
+
+/// Overwrites `buf` with zeros.  This is used by the `zeroize` feature to
+/// clear buffers that this crate owns, and callers can use it to clear their
+/// own.
+///
+/// As with [`ct_eq`], this is best-effort: without unsafe code, there is no
+/// way to prevent the optimizer from removing writes to memory that is never
+/// read again.  A compiler fence makes this much less likely.
+///
+/// ```rust
+/// # use openpgp_parser::util::zeroize;
+/// let mut buf = *b"secret";
+/// zeroize(&mut buf);
+/// assert_eq!(buf, [0; 6]);
+/// ```
+pub fn zeroize(buf: &mut [u8]) {
+    for byte in buf.iter_mut() {
+        *byte = 0
+    }
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ct_eq_matches_eq() {
+        let values: &[&[u8]] = &[b"", b"\0", b"\x01", b"\0\0", b"\x80\0", b"\0\x80", b"abcd"];
+        for a in values {
+            for b in values {
+                assert_eq!(ct_eq(a, b), a == b);
+            }
+        }
+    }
+}
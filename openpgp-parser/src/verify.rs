@@ -0,0 +1,330 @@
+//! Checking signatures against public keys
+//!
+//! `openpgp-parser` has no public-key cryptography of its own.  As with
+//! [`super::hashing::Digest`], the caller provides it, through the
+//! [`Verifier`] trait.  [`Signature::verify_digest`] does the OpenPGP part of
+//! the check: it finishes the hash, compares it with the signature's hash
+//! prefix, takes the key and signature apart, and calls the [`Verifier`]
+//! method for the public-key algorithm.
+
+use super::algorithm::{HashAlgorithm, PublicKeyAlgorithm};
+use super::hashing::Digest;
+use super::key::PublicKey;
+use super::signature::Signature;
+use super::{Error, Reader};
+
+/// Public-key signature verification, provided by the caller
+///
+/// Every method gets the final digest, the key material, and the signature,
+/// with each integer as a minimal big-endian MPI value (so it may be shorter
+/// than the field or modulus size).  It returns [`true`] if the signature is
+/// valid; a backend that does not support an algorithm returns [`false`].
+pub trait Verifier {
+    /// Verifies an RSA PKCS#1 v1.5 signature `s` over `digest`, which was
+    /// computed with `hash`, using the modulus `n` and public exponent `e`
+    fn verify_rsa(&self, hash: HashAlgorithm, digest: &[u8], n: &[u8], e: &[u8], s: &[u8]) -> bool;
+    /// Verifies a DSA signature (`r`, `s`) over `digest`.  `key` holds `p`,
+    /// `q`, `g`, and `y`, in that order.
+    fn verify_dsa(&self, digest: &[u8], key: [&[u8]; 4], r: &[u8], s: &[u8]) -> bool;
+    /// Verifies an ECDSA signature (`r`, `s`) over `digest`, using the point
+    /// `point` on the curve identified by `curve_oid`
+    fn verify_ecdsa(
+        &self,
+        digest: &[u8],
+        curve_oid: &[u8],
+        point: &[u8],
+        r: &[u8],
+        s: &[u8],
+    ) -> bool;
+    /// Verifies an EdDSA signature (`r`, `s`) over `digest`, using the point
+    /// `point` on the curve identified by `curve_oid`.  For Ed25519, `point`
+    /// is the byte 0x40 followed by the 32-byte public key.
+    fn verify_eddsa(
+        &self,
+        digest: &[u8],
+        curve_oid: &[u8],
+        point: &[u8],
+        r: &[u8],
+        s: &[u8],
+    ) -> bool;
+}
+
+impl<'a> Signature<'a> {
+    /// Checks this signature against `key`, using `verifier` for the
+    /// public-key operation.
+    ///
+    /// `digest` must be a fresh hash context of this signature's
+    /// [`Self::hash_algorithm`] that has already been fed the signed data.
+    /// This adds [`Self::hashed_material`] and, for version 4 signatures,
+    /// [`Self::trailer`], then finalizes it.
+    ///
+    /// Returns [`Error::BadSignature`] if `key` is for a different public-key
+    /// algorithm, if the digest has the wrong length or does not match
+    /// [`Self::hash_prefix`], or if `verifier` rejects the signature.  The
+    /// verifier is not called in the first two cases.  This does not check
+    /// that `key` is the key that [`Self::issuer_key_id`] names, or whether
+    /// the signature has expired.
+    pub fn verify_digest<H, V>(
+        &self,
+        key: &PublicKey,
+        mut digest: H,
+        verifier: &V,
+    ) -> Result<(), Error>
+    where
+        H: Digest,
+        H::Output: AsRef<[u8]>,
+        V: Verifier,
+    {
+        if self.pk_algorithm() != key.algorithm() {
+            return Err(Error::BadSignature);
+        }
+        digest.update(self.hashed_material());
+        if let Some(trailer) = self.trailer() {
+            digest.update(&trailer);
+        }
+        let digest = digest.finalize();
+        let digest = digest.as_ref();
+        let hash = HashAlgorithm::from_u8(self.hash_algorithm());
+        if hash.digest_len() != Some(digest.len()) || !self.quick_check(digest) {
+            return Err(Error::BadSignature);
+        }
+        let valid = match PublicKeyAlgorithm::from_u8(key.algorithm()) {
+            PublicKeyAlgorithm::Rsa | PublicKeyAlgorithm::RsaSign => {
+                let (n, e) = Reader::read_all_strict(key.mpis(), |reader| {
+                    Ok((reader.mpi()?, reader.mpi()?))
+                })
+                .map_err(|_| Error::IllFormedKey)?;
+                let s = Reader::read_all_strict(self.mpis(), Reader::mpi)
+                    .map_err(|_| Error::IllFormedSignature)?;
+                verifier.verify_rsa(hash, digest, n, e, s)
+            }
+            PublicKeyAlgorithm::Dsa => {
+                let key = Reader::read_all_strict(key.mpis(), |reader| {
+                    Ok([reader.mpi()?, reader.mpi()?, reader.mpi()?, reader.mpi()?])
+                })
+                .map_err(|_| Error::IllFormedKey)?;
+                let (r, s) = self
+                    .dsa_signature_components()
+                    .ok_or(Error::IllFormedSignature)?;
+                verifier.verify_dsa(digest, key, r, s)
+            }
+            alg @ PublicKeyAlgorithm::Ecdsa | alg @ PublicKeyAlgorithm::EdDsa => {
+                let curve_oid = key.curve_oid().ok_or(Error::IllFormedKey)?;
+                let point = Reader::read_all_strict(key.mpis(), |reader| {
+                    let oid_len = reader.byte()?;
+                    reader.skip(oid_len.into())?;
+                    reader.mpi()
+                })
+                .map_err(|_| Error::IllFormedKey)?;
+                let (r, s) = self
+                    .ec_signature_components()
+                    .ok_or(Error::IllFormedSignature)?;
+                if alg == PublicKeyAlgorithm::Ecdsa {
+                    verifier.verify_ecdsa(digest, curve_oid, point, r, s)
+                } else {
+                    verifier.verify_eddsa(digest, curve_oid, point, r, s)
+                }
+            }
+            alg => return Err(Error::UnsupportedPkeyAlgorithm(alg.to_u8())),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::BadSignature)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::packet::Packet;
+    use super::super::rpm::{self, Header};
+    use super::*;
+    static RPM: &[u8] = include_bytes!("../../data/lua-5.4.2-1.fc33.x86_64.rpm");
+
+    /// The arguments a [`Verifier`] method is expected to be called with
+    #[derive(Debug, PartialEq)]
+    enum Call<'a> {
+        Rsa(HashAlgorithm, &'a [u8], &'a [u8], &'a [u8], &'a [u8]),
+        Dsa(&'a [u8], [&'a [u8]; 4], &'a [u8], &'a [u8]),
+        Ecdsa(&'a [u8], &'a [u8], &'a [u8], &'a [u8], &'a [u8]),
+        EdDsa(&'a [u8], &'a [u8], &'a [u8], &'a [u8], &'a [u8]),
+    }
+
+    /// A backend that checks its arguments and returns a fixed answer
+    struct Expect<'a>(Call<'a>, bool);
+    impl<'a> Verifier for Expect<'a> {
+        fn verify_rsa(
+            &self,
+            hash: HashAlgorithm,
+            digest: &[u8],
+            n: &[u8],
+            e: &[u8],
+            s: &[u8],
+        ) -> bool {
+            assert_eq!(Call::Rsa(hash, digest, n, e, s), self.0);
+            self.1
+        }
+        fn verify_dsa(&self, digest: &[u8], key: [&[u8]; 4], r: &[u8], s: &[u8]) -> bool {
+            assert_eq!(Call::Dsa(digest, key, r, s), self.0);
+            self.1
+        }
+        fn verify_ecdsa(
+            &self,
+            digest: &[u8],
+            oid: &[u8],
+            point: &[u8],
+            r: &[u8],
+            s: &[u8],
+        ) -> bool {
+            assert_eq!(Call::Ecdsa(digest, oid, point, r, s), self.0);
+            self.1
+        }
+        fn verify_eddsa(
+            &self,
+            digest: &[u8],
+            oid: &[u8],
+            point: &[u8],
+            r: &[u8],
+            s: &[u8],
+        ) -> bool {
+            assert_eq!(Call::EdDsa(digest, oid, point, r, s), self.0);
+            self.1
+        }
+    }
+
+    /// A "hash" of one known input, whose value was computed elsewhere
+    struct Known {
+        data: Vec<u8>,
+        input: Vec<u8>,
+        output: [u8; 32],
+    }
+    impl Digest for Known {
+        type Output = [u8; 32];
+        fn update(&mut self, data: &[u8]) {
+            self.data.extend_from_slice(data)
+        }
+        fn finalize(self) -> [u8; 32] {
+            assert!(self.data == self.input, "unexpected data hashed");
+            self.output
+        }
+    }
+
+    /// SHA-256 of the main header of the RPM, the hashed part of its
+    /// RSAHEADER signature, and the trailer
+    const HEADER_DIGEST: [u8; 32] = [
+        0x68, 0xC5, 0xA6, 0xE5, 0x81, 0x23, 0x17, 0xED, 0x99, 0x28, 0xCD, 0xDB, 0xF5, 0x8D, 0x26,
+        0xF0, 0x8B, 0x87, 0xD5, 0xB6, 0xBE, 0xE1, 0xF1, 0xB2, 0x1B, 0x2F, 0x61, 0xE8, 0x87, 0xB9,
+        0xA9, 0xA4,
+    ];
+
+    #[test]
+    fn rpm_header_signature() {
+        let sig_header = Header::parse(&mut Reader::new(&RPM[96..])).unwrap();
+        let data = sig_header.entry(rpm::RPMSIGTAG_RSAHEADER).unwrap();
+        let sig = rpm::parse_rpm_signature(data.data().unwrap()).unwrap();
+        // the RSAHEADER signature covers the main header, which follows the
+        // signature header and its padding
+        let main_header = &RPM[4504..4504 + 7997];
+        let mut input = main_header.to_vec();
+        input.extend_from_slice(&RPM[259..294]);
+        input.extend_from_slice(&[4, 0xFF, 0, 0, 0, 35]);
+        let hash = || Known {
+            data: main_header.to_vec(),
+            input: input.clone(),
+            output: HEADER_DIGEST,
+        };
+        // The Fedora 33 key is not in data/, so check the path with a 4096-bit
+        // RSA key that the backend only has to compare
+        let n = [0xC5; 512];
+        let mut body = vec![4, 0x5F, 0x5C, 0x08, 0x0C, 1, 0x10, 0];
+        body.extend_from_slice(&n);
+        body.extend_from_slice(&[0, 17, 1, 0, 1]);
+        let packet = Packet::new(6, &body).unwrap();
+        let key = PublicKey::parse(&packet).unwrap();
+        let s = &RPM[310..822];
+        let call = || Call::Rsa(HashAlgorithm::Sha256, &HEADER_DIGEST, &n, &[1, 0, 1], s);
+        assert_eq!(
+            sig.verify_digest(&key, hash(), &Expect(call(), true)),
+            Ok(())
+        );
+        assert_eq!(
+            sig.verify_digest(&key, hash(), &Expect(call(), false)),
+            Err(Error::BadSignature)
+        );
+        // a digest that does not match the hash prefix never reaches the backend
+        let mut wrong = hash();
+        wrong.output[0] ^= 1;
+        let unreachable = Expect(Call::Dsa(&[], [&[]; 4], &[], &[]), true);
+        assert_eq!(
+            sig.verify_digest(&key, wrong, &unreachable),
+            Err(Error::BadSignature)
+        );
+        // the same checks, starting from the raw header and signature
+        let verify = |header: &[u8], expect: &Expect| {
+            let digest = Known {
+                data: Vec::new(),
+                input: input.clone(),
+                output: HEADER_DIGEST,
+            };
+            rpm::verify_rpm_header(header, data.data().unwrap(), &key, digest, expect)
+        };
+        assert_eq!(verify(main_header, &Expect(call(), true)), Ok(()));
+        assert_eq!(
+            verify(main_header, &Expect(call(), false)),
+            Err(Error::BadSignature)
+        );
+        assert_eq!(
+            verify(&RPM[4504..4504 + 7998], &unreachable),
+            Err(Error::TrailingJunk { offset: 7997 })
+        );
+        // the data section is one byte short
+        assert_eq!(
+            verify(&main_header[..7996], &unreachable),
+            Err(Error::Truncated {
+                needed: 6989,
+                available: 6988
+            })
+        );
+    }
+
+    #[cfg(feature = "sha1")]
+    #[test]
+    fn sha1_eddsa_certification() {
+        use super::super::key::user_id_binding_input;
+        use super::super::packet;
+        use super::super::sha1::Sha1;
+        static KEY: &[u8] = include_bytes!("../../data/sha1-cert-key.gpg");
+        let packets: Vec<_> = packet::PacketIter::new(Reader::new(KEY))
+            .map(Result::unwrap)
+            .collect();
+        let key = PublicKey::parse(&packets[0]).unwrap();
+        let sig = Signature::parse(&packets[2]).unwrap();
+        let hash = || {
+            let mut ctx = Sha1::new();
+            ctx.update(&user_id_binding_input(&key, &packets[1]));
+            ctx
+        };
+        // checked with a real Ed25519 implementation
+        let digest = [
+            0xD8, 0xD3, 0xFF, 0xD3, 0x35, 0xE2, 0xD9, 0x87, 0xA6, 0x82, 0x41, 0x06, 0x77, 0x45,
+            0xB2, 0x9F, 0x94, 0x71, 0x2C, 0xFC,
+        ];
+        let call = Call::EdDsa(
+            &digest,
+            &KEY[9..18],
+            &KEY[20..53],
+            &KEY[164..196],
+            &KEY[198..230],
+        );
+        assert_eq!(sig.verify_digest(&key, hash(), &Expect(call, true)), Ok(()));
+        // a key for another algorithm cannot have made the signature
+        let rsa = [4, 0, 0, 0, 0, 1, 0, 1, 1, 0, 1, 1];
+        let rsa = PublicKey::parse(&Packet::new(6, &rsa).unwrap()).unwrap();
+        let unreachable = Expect(Call::Dsa(&[], [&[]; 4], &[], &[]), true);
+        assert_eq!(
+            sig.verify_digest(&rsa, hash(), &unreachable),
+            Err(Error::BadSignature)
+        );
+    }
+}
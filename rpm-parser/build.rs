@@ -14,6 +14,7 @@ fn main() {
     let mut ellipsis_inclusive_range_deprecated = true;
     let mut bare_trait_obj_deprecated = true;
     let mut alloc_crate_unstable = false;
+    let mut const_generics_unstable = false;
     if version.starts_with("rustc 1.") {
         let version = &version[8..];
         if let Some(period) = version.find('.') {
@@ -23,6 +24,7 @@ fn main() {
                 alloc_crate_unstable = vnum < 36;
                 bare_trait_obj_deprecated = vnum >= 37;
                 ellipsis_inclusive_range_deprecated = vnum >= 37;
+                const_generics_unstable = vnum < 51;
             }
         }
     }
@@ -53,4 +55,9 @@ fn main() {
     } else {
         println!("cargo:rustc-cfg=alloc_crate_stable");
     }
+    if const_generics_unstable {
+        println!("cargo:rustc-cfg=const_generics_unstable");
+    } else {
+        println!("cargo:rustc-cfg=const_generics_stable");
+    }
 }